@@ -1,4 +1,4 @@
-use criterion::{Criterion, criterion_main, criterion_group, Fun};
+use criterion::{criterion_group, criterion_main, Criterion};
 use two_body::{TwoBodySystem2d, Body2d};
 
 fn create_system() -> TwoBodySystem2d {
@@ -26,22 +26,40 @@ fn criterion_benchmark(c: &mut Criterion) {
     let h = 0.1;
 
     let mut rk4 = system.construct_rk4(h);
-    let mut euler = system.construct_euler(h);
+    let mut explicit_euler = system.construct_explicit_euler(h);
+    let mut heun = system.construct_heun(h);
+    let mut implicit_euler = system.construct_implicit_euler(h);
     let mut rk45 = system.construct_rk45(h, 0.00001, 10000000.0);
-    
-    // Hack: get 2nd init parameter for ab2 and am2
-    let next_step = rk4.next().unwrap();
-
-    let mut ab2 = system.construct_ab2(h, next_step);
-    let mut am2 = system.construct_am2(h, next_step);
+    let mut dopri5 = system.construct_dopri5(h, 0.00001, 10000000.0);
+    let mut gbs = system.construct_gbs(h, 0.00001, 10000000.0);
+    let mut rkn4 = system.construct_rkn4(h);
+    let mut rkn64 = system.construct_rkn64(h, 0.00001, 10000000.0);
+    let mut verlet = system.construct_verlet(h);
+    let mut yoshida4 = system.construct_yoshida4(h);
+    let mut yoshida6 = system.construct_yoshida6(h);
+    let mut ab2 = system.construct_ab2(h);
+    let mut am2 = system.construct_am2(h);
+    let mut abm8 = system.construct_abm(h, 8);
+    let mut gauss4 = system.construct_gauss_legendre(h, 4);
 
     let mut group = c.benchmark_group("Solvers");
 
     group.bench_function("Rk4", |b| b.iter(|| rk4.next()));
-    group.bench_function("Euler", |b| b.iter(|| euler.next()));
+    group.bench_function("ExplicitEuler", |b| b.iter(|| explicit_euler.next()));
+    group.bench_function("Heun", |b| b.iter(|| heun.next()));
+    group.bench_function("ImplicitEuler", |b| b.iter(|| implicit_euler.next()));
     group.bench_function("Rk45", |b| b.iter(|| rk45.next()));
+    group.bench_function("Dopri5", |b| b.iter(|| dopri5.next()));
+    group.bench_function("Gbs", |b| b.iter(|| gbs.next()));
+    group.bench_function("Rkn4", |b| b.iter(|| rkn4.next()));
+    group.bench_function("Rkn64", |b| b.iter(|| rkn64.next()));
     group.bench_function("Ab2", |b| b.iter(|| ab2.next()));
     group.bench_function("Am2", |b| b.iter(|| am2.next()));
+    group.bench_function("Abm8", |b| b.iter(|| abm8.next()));
+    group.bench_function("Gauss4", |b| b.iter(|| gauss4.next()));
+    group.bench_function("Verlet", |b| b.iter(|| verlet.next()));
+    group.bench_function("Yoshida4", |b| b.iter(|| yoshida4.next()));
+    group.bench_function("Yoshida6", |b| b.iter(|| yoshida6.next()));
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,288 @@
+use std::f64::consts::PI;
+
+/// Tolerance of the parabolic orbit detection: orbit is
+/// parabolic if `|e - 1| < PARABOLIC_TOLERANCE`
+pub const PARABOLIC_TOLERANCE: f64 = 1e-10;
+
+/// Max iterations of Kepler's equation solver
+///
+/// Newton's method converges in a few iterations with the used
+/// starters, this is just a safeguard
+const MAX_ITER: usize = 100;
+
+/// Type of the conic section of the orbit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Conic {
+    Elliptic,
+    Parabolic,
+    Hyperbolic,
+}
+
+impl Conic {
+    pub fn from_eccentricity(e: f64) -> Self {
+        if (e - 1.0).abs() < PARABOLIC_TOLERANCE {
+            Conic::Parabolic
+        } else if e < 1.0 {
+            Conic::Elliptic
+        } else {
+            Conic::Hyperbolic
+        }
+    }
+}
+
+/// Solution of Kepler's equation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeplerSolution {
+    /// Found anomaly
+    pub anomaly: f64,
+    /// Residual of the equation for `anomaly`
+    pub residual: f64,
+    /// Number of iterations
+    pub iterations: usize,
+}
+
+/// Solve `f(x) = 0` for increasing function `f` with the root
+/// in [`low`, `high`] by Newton's method starting from `x`.
+///
+/// Steps out of the bracket are replaced with bisection,
+/// so the method always converges.
+///
+/// `f` returns (f(x), f'(x))
+fn solve<F>(mut f: F, mut low: f64, mut high: f64, mut x: f64, scale: f64) -> KeplerSolution
+where
+    F: FnMut(f64) -> (f64, f64),
+{
+    let tolerance = 4.0 * f64::EPSILON * scale.max(1.0);
+
+    let (mut fx, mut dfx) = f(x);
+    let mut iterations = 0;
+
+    while iterations < MAX_ITER && fx.abs() > tolerance && high - low > f64::EPSILON * x.abs() {
+        iterations += 1;
+
+        if fx > 0.0 {
+            high = x;
+        } else {
+            low = x;
+        }
+
+        let newton = x - fx / dfx;
+
+        x = if newton > low && newton < high {
+            newton
+        } else {
+            (low + high) / 2.0
+        };
+
+        let value = f(x);
+        fx = value.0;
+        dfx = value.1;
+    }
+
+    KeplerSolution {
+        anomaly: x,
+        residual: fx,
+        iterations,
+    }
+}
+
+/// Mean anomaly M = E - e * sin(E) of elliptic orbit
+pub fn eccentric_to_mean(ea: f64, e: f64) -> f64 {
+    ea - e * ea.sin()
+}
+
+/// Solve Kepler's equation M = E - e * sin(E) for elliptic orbit
+pub fn mean_to_eccentric(m: f64, e: f64) -> KeplerSolution {
+    // Reduce M to [-PI, PI]
+    let turns = (m / (2.0 * PI)).round();
+    let reduced = m - turns * 2.0 * PI;
+
+    // E - M = e * sin(E) has the same sign as M, so E
+    // is between M and M + e * sign(M). Danby's starter.
+    let sign = if reduced < 0.0 { -1.0 } else { 1.0 };
+    let (low, high) = if sign > 0.0 {
+        (reduced, (reduced + e).min(PI))
+    } else {
+        ((reduced - e).max(-PI), reduced)
+    };
+    let start = (reduced + sign * 0.85 * e).max(low).min(high);
+
+    let mut solution = solve(
+        |x| (x - e * x.sin() - reduced, 1.0 - e * x.cos()),
+        low,
+        high,
+        start,
+        PI,
+    );
+
+    solution.anomaly += turns * 2.0 * PI;
+    solution
+}
+
+/// Eccentric anomaly E of elliptic orbit from the true anomaly `nu`
+pub fn true_to_eccentric(nu: f64, e: f64) -> f64 {
+    let (s, c) = (nu / 2.0).sin_cos();
+    2.0 * ((1.0 - e).sqrt() * s).atan2((1.0 + e).sqrt() * c)
+}
+
+/// True anomaly of elliptic orbit from the eccentric anomaly `ea`
+pub fn eccentric_to_true(ea: f64, e: f64) -> f64 {
+    let (s, c) = (ea / 2.0).sin_cos();
+    2.0 * ((1.0 + e).sqrt() * s).atan2((1.0 - e).sqrt() * c)
+}
+
+/// Mean anomaly M = e * sinh(H) - H of hyperbolic orbit
+pub fn hyperbolic_to_mean(ha: f64, e: f64) -> f64 {
+    e * ha.sinh() - ha
+}
+
+/// Solve Kepler's equation M = e * sinh(H) - H for hyperbolic orbit
+pub fn mean_to_hyperbolic(m: f64, e: f64) -> KeplerSolution {
+    // Solve for |M| and restore the sign: equation is odd.
+    // From e * sinh(H) >= M and (e - 1) * sinh(H) <= M
+    // asinh(M / e) <= H <= asinh(M / (e - 1))
+    let abs_m = m.abs();
+    let low = (abs_m / e).asinh();
+    let high = (abs_m / (e - 1.0)).asinh();
+    let start = (2.0 * abs_m / e + 1.8).ln().max(low).min(high);
+
+    let mut solution = solve(
+        |x| (e * x.sinh() - x - abs_m, e * x.cosh() - 1.0),
+        low,
+        high,
+        start,
+        abs_m,
+    );
+
+    if m < 0.0 {
+        solution.anomaly = -solution.anomaly;
+        solution.residual = -solution.residual;
+    }
+
+    solution
+}
+
+/// Hyperbolic anomaly H from the true anomaly `nu`
+pub fn true_to_hyperbolic(nu: f64, e: f64) -> f64 {
+    2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * (nu / 2.0).tan()).atanh()
+}
+
+/// True anomaly of hyperbolic orbit from the hyperbolic anomaly `ha`
+pub fn hyperbolic_to_true(ha: f64, e: f64) -> f64 {
+    2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (ha / 2.0).tanh()).atan()
+}
+
+/// Mean anomaly M = D + D^3 / 3 of parabolic orbit (Barker's equation)
+pub fn parabolic_to_mean(da: f64) -> f64 {
+    da + da.powi(3) / 3.0
+}
+
+/// Solve Barker's equation M = D + D^3 / 3 for parabolic orbit
+///
+/// The equation has the only real root, so it is found exactly
+pub fn mean_to_parabolic(m: f64) -> KeplerSolution {
+    let b = 1.5 * m;
+    let y = (b + (b * b + 1.0).sqrt()).cbrt();
+    let da = y - 1.0 / y;
+
+    KeplerSolution {
+        anomaly: da,
+        residual: parabolic_to_mean(da) - m,
+        iterations: 0,
+    }
+}
+
+/// Parabolic anomaly D = tan(nu / 2) from the true anomaly `nu`
+pub fn true_to_parabolic(nu: f64) -> f64 {
+    (nu / 2.0).tan()
+}
+
+/// True anomaly of parabolic orbit from the parabolic anomaly `da`
+pub fn parabolic_to_true(da: f64) -> f64 {
+    2.0 * da.atan()
+}
+
+/// Mean anomaly from the true anomaly `nu` for any type of orbit
+pub fn true_to_mean(nu: f64, e: f64) -> f64 {
+    match Conic::from_eccentricity(e) {
+        Conic::Elliptic => eccentric_to_mean(true_to_eccentric(nu, e), e),
+        Conic::Hyperbolic => hyperbolic_to_mean(true_to_hyperbolic(nu, e), e),
+        Conic::Parabolic => parabolic_to_mean(true_to_parabolic(nu)),
+    }
+}
+
+/// True anomaly in (-PI, PI] from the mean anomaly `m` for any type of orbit
+///
+/// `anomaly` of the result is the true anomaly while `residual` and
+/// `iterations` are from Kepler's equation
+pub fn mean_to_true(m: f64, e: f64) -> KeplerSolution {
+    let mut solution;
+
+    match Conic::from_eccentricity(e) {
+        Conic::Elliptic => {
+            solution = mean_to_eccentric(m, e);
+            solution.anomaly = eccentric_to_true(solution.anomaly, e);
+        }
+        Conic::Hyperbolic => {
+            solution = mean_to_hyperbolic(m, e);
+            solution.anomaly = hyperbolic_to_true(solution.anomaly, e);
+        }
+        Conic::Parabolic => {
+            solution = mean_to_parabolic(m);
+            solution.anomaly = parabolic_to_true(solution.anomaly);
+        }
+    }
+
+    solution.anomaly = (solution.anomaly + PI).rem_euclid(2.0 * PI) - PI;
+    solution
+}
+
+#[test]
+fn test_mean_to_eccentric() {
+    for &e in &[0.0, 0.1, 0.5, 0.9, 0.99, 0.999999] {
+        for k in -50..=50 {
+            let m = k as f64 * 0.13;
+            let solution = mean_to_eccentric(m, e);
+
+            assert!(solution.residual.abs() < 1e-14, "e = {}, M = {}", e, m);
+            assert!((eccentric_to_mean(solution.anomaly, e) - m).abs() < 1e-13);
+            assert!(solution.iterations < 60);
+        }
+    }
+}
+
+#[test]
+fn test_mean_to_hyperbolic() {
+    for &e in &[1.000001, 1.01, 1.5, 3.0, 100.0] {
+        for &m in &[-1000.0, -5.0, -0.1, 0.0, 1e-8, 0.3, 2.0, 50.0, 1e6] {
+            let solution = mean_to_hyperbolic(m, e);
+
+            assert!(solution.residual.abs() <= 1e-14 * f64::max(1.0, m.abs()));
+            assert!(solution.iterations < 60);
+        }
+    }
+}
+
+#[test]
+fn test_anomaly_round_trip() {
+    for &e in &[0.0, 0.3, 0.95, 1.0, 1.2, 4.0] {
+        for k in -9..=9 {
+            let nu = k as f64 * 0.3;
+
+            // Out of the asymptotes of hyperbola
+            if 1.0 + e * nu.cos() <= 0.0 {
+                continue;
+            }
+
+            let m = true_to_mean(nu, e);
+            let solution = mean_to_true(m, e);
+
+            assert!(
+                (solution.anomaly - nu).abs() < 1e-9,
+                "e = {}, nu = {}",
+                e,
+                nu
+            );
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::kepler::KeplerError;
+use crate::vector::Vector3;
+use std::f64::consts::PI;
+
+/// Relative tolerance of the angular momentum:
+/// orbit is degenerate if `|h| < DEGENERATE_TOLERANCE * |r| * |v|`
+const DEGENERATE_TOLERANCE: f64 = 1e-12;
+
+/// Orbit is circular if `e < SINGULAR_TOLERANCE` and
+/// equatorial if `sin(i) < SINGULAR_TOLERANCE`
+const SINGULAR_TOLERANCE: f64 = 1e-10;
+
+pub(crate) fn vec_len(vec: Vector3) -> f64 {
+    scalar_mul(vec, vec).sqrt()
+}
+
+pub(crate) fn scalar_mul(v1: Vector3, v2: Vector3) -> f64 {
+    v1[0] * v2[0] + v1[1] * v2[1] + v1[2] * v2[2]
+}
+
+/// Angle from `from` to `to` around `axis` (unit vector) in (-PI, PI]
+fn angle_around(from: Vector3, to: Vector3, axis: Vector3) -> f64 {
+    scalar_mul(from * to, axis).atan2(scalar_mul(from, to))
+}
+
+/// Angle in [0, 2 * PI)
+fn normalize(angle: f64) -> f64 {
+    let result = angle.rem_euclid(2.0 * PI);
+    if result >= 2.0 * PI {
+        0.0
+    } else {
+        result
+    }
+}
+
+/// Classical orbital elements
+///
+/// Semi-latus rectum is used instead of semi-major axis
+/// so parabolic orbits are described too.
+///
+/// Angles are undefined for some orbits, so
+///
+/// - for circular orbit `w` is 0 and `nu` is the argument of latitude
+///
+/// - for equatorial orbit `omega` is 0 and `w` is the longitude of periapsis
+///
+/// - for circular equatorial orbit `omega` and `w` are 0 and `nu`
+///   is the true longitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitalElements {
+    /// Semi-latus rectum
+    pub p: f64,
+    /// Eccentricity
+    pub e: f64,
+    /// Inclination in [0, PI]
+    pub i: f64,
+    /// Longitude of ascending node (LAN) in [0, 2 * PI)
+    pub omega: f64,
+    /// Argument of periapsis in [0, 2 * PI)
+    pub w: f64,
+    /// True anomaly in (-PI, PI]
+    pub nu: f64,
+}
+
+impl OrbitalElements {
+    /// Elements of orbit with semi-major axis `a` (negative
+    /// for hyperbolic orbit)
+    pub fn with_semi_major_axis(a: f64, e: f64, i: f64, omega: f64, w: f64, nu: f64) -> Self {
+        Self {
+            p: a * (1.0 - e * e),
+            e,
+            i,
+            omega,
+            w,
+            nu,
+        }
+    }
+
+    /// Calculate elements from position `r` and velocity `v`
+    /// with std gravitational parameter `mu`
+    pub fn from_state(r: Vector3, v: Vector3, mu: f64) -> Result<Self, KeplerError> {
+        if mu <= 0.0 || mu.is_nan() {
+            return Err(KeplerError::NonPositiveMu(mu));
+        }
+
+        // 1. a) Calculate orbital momentum vector h
+        let h = r * v;
+        let h_len = vec_len(h);
+
+        if h_len <= DEGENERATE_TOLERANCE * vec_len(r) * vec_len(v) {
+            return Err(KeplerError::Degenerate);
+        }
+
+        let axis = h / h_len;
+
+        // 1. b) Obtain the eccentricity vector e
+        let e_vec = (v * h) / mu - r / vec_len(r);
+
+        // 1. c) Determine the vector n pointing towards the asscending node
+        let n: Vector3 = [-h[1], h[0], 0.0].into();
+
+        // 2. Calculate the orbit inclination i
+        let i = (h[2] / h_len).clamp(-1.0, 1.0).acos();
+
+        // 3. Determine the orbit eccentricity e and the semi-latus rectum p
+        let e = vec_len(e_vec);
+        let p = h_len * h_len / mu;
+
+        // 4. Obtain the longitute of ascending node omega. Node of
+        // equatorial orbit is replaced with x axis
+        let equatorial = vec_len(n) < SINGULAR_TOLERANCE * h_len;
+
+        let (omega, node) = if equatorial {
+            (0.0, Vector3::from([1.0, 0.0, 0.0]))
+        } else {
+            (normalize(n[1].atan2(n[0])), n)
+        };
+
+        // 5. Obtain the argument of periapsis w and the true anomaly nu.
+        // Periapsis of circular orbit is replaced with node
+        let (w, nu) = if e < SINGULAR_TOLERANCE {
+            (0.0, angle_around(node, r, axis))
+        } else {
+            (
+                normalize(angle_around(node, e_vec, axis)),
+                angle_around(e_vec, r, axis),
+            )
+        };
+
+        Ok(Self {
+            p,
+            e,
+            i,
+            omega,
+            w,
+            nu,
+        })
+    }
+
+    /// Calculate position and velocity
+    /// with std gravitational parameter `mu`
+    ///
+    /// # Returns
+    ///
+    /// (r, v) vectors
+    pub fn to_state(&self, mu: f64) -> (Vector3, Vector3) {
+        // 1. Obtain the position and velocity in the orbital plane
+        let rc = self.p / (1.0 + self.e * self.nu.cos());
+        let ot = Vector3::from([self.nu.cos(), self.nu.sin(), 0.0]) * rc;
+        let vt =
+            Vector3::from([-self.nu.sin(), self.e + self.nu.cos(), 0.0]) * (mu / self.p).sqrt();
+
+        // 2. Transform them to the rectangular coordinates
+        (self.rotate(ot), self.rotate(vt))
+    }
+
+    /// Semi-major axis (negative for hyperbolic orbit,
+    /// infinite for parabolic orbit)
+    pub fn semi_major_axis(&self) -> f64 {
+        self.p / (1.0 - self.e * self.e)
+    }
+
+    /// Transform vector `o` from the orbital plane
+    /// to the rectangular coordinates
+    fn rotate(&self, o: Vector3) -> Vector3 {
+        let (sw, cw) = self.w.sin_cos();
+        let (so, co) = self.omega.sin_cos();
+        let (si, ci) = self.i.sin_cos();
+
+        let x = o[0] * (cw * co - sw * ci * so) - o[1] * (sw * co + cw * ci * so);
+
+        let y = o[0] * (cw * so + sw * ci * co) + o[1] * (cw * ci * co - sw * so);
+
+        let z = o[0] * (sw * si) + o[1] * (cw * si);
+
+        [x, y, z].into()
+    }
+}
+
+#[test]
+fn test_elements_round_trip() {
+    let mu = 2.5;
+
+    let states: [([f64; 3], [f64; 3]); 6] = [
+        // Inclined elliptic
+        ([1.0, 0.2, -0.3], [0.1, 1.2, 0.4]),
+        // Hyperbolic
+        ([1.0, 0.2, -0.3], [0.1, 2.5, 0.4]),
+        // Circular equatorial
+        ([2.0, 0.0, 0.0], [0.0, (mu / 2.0f64).sqrt(), 0.0]),
+        // Circular inclined
+        (
+            [0.0, 2.0, 0.0],
+            [-(mu / 4.0f64).sqrt(), 0.0, (mu / 4.0f64).sqrt()],
+        ),
+        // Elliptic retrograde equatorial
+        ([-1.0, 1.0, 0.0], [0.3, 1.1, 0.0]),
+        // Parabolic
+        ([1.0, 0.0, 0.0], [0.0, (2.0 * mu).sqrt(), 0.0]),
+    ];
+
+    for (r, v) in states.iter() {
+        let r0 = Vector3::from(*r);
+        let v0 = Vector3::from(*v);
+
+        let elements = OrbitalElements::from_state(r0, v0, mu).unwrap();
+        let (r1, v1) = elements.to_state(mu);
+
+        assert!(vec_len(r1 - r0) < 1e-12, "{:?}", elements);
+        assert!(vec_len(v1 - v0) < 1e-12, "{:?}", elements);
+    }
+}
+
+#[test]
+fn test_elements_from_state() {
+    let elements = OrbitalElements::with_semi_major_axis(2.0, 0.5, 0.3, 1.0, 2.0, -0.5);
+    let (r, v) = elements.to_state(1.0);
+
+    let result = OrbitalElements::from_state(r, v, 1.0).unwrap();
+
+    assert!((result.semi_major_axis() - 2.0).abs() < 1e-12);
+    assert!((result.e - 0.5).abs() < 1e-12);
+    assert!((result.i - 0.3).abs() < 1e-12);
+    assert!((result.omega - 1.0).abs() < 1e-12);
+    assert!((result.w - 2.0).abs() < 1e-12);
+    assert!((result.nu + 0.5).abs() < 1e-12);
+}
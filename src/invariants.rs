@@ -0,0 +1,143 @@
+use crate::elements::{scalar_mul, vec_len};
+use crate::twobody::{split_state, Dim, Space, State, VType};
+use crate::vector::Vector3;
+
+/// Invariants of the two-body problem
+#[derive(Debug, Clone, Copy)]
+pub struct Invariants {
+    /// Specific orbital energy v^2 / 2 - mu / r
+    pub energy: VType,
+    /// Specific angular momentum r x v
+    pub angular_momentum: Vector3,
+    /// Eccentricity vector (Laplace–Runge–Lenz vector divided by `mu`)
+    /// (v x h) / mu - r / |r|
+    pub eccentricity: Vector3,
+}
+
+/// Drift of the invariants from their init values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drift {
+    /// Relative drift of the energy (absolute if init energy is zero)
+    pub energy: VType,
+    /// Relative drift of the angular momentum vector
+    /// (absolute if init angular momentum is zero)
+    pub angular_momentum: VType,
+    /// Absolute drift of the eccentricity vector. Eccentricity
+    /// is dimensionless and zero for circular orbit, so relative
+    /// drift makes no sense
+    pub eccentricity: VType,
+}
+
+/// `delta / |init|` or `delta` if `init` is zero
+fn relative(delta: VType, init: VType) -> VType {
+    if init == 0.0 {
+        delta
+    } else {
+        delta / init.abs()
+    }
+}
+
+impl Invariants {
+    /// Calculate invariants for position `r` and velocity `v`
+    /// with std gravitational parameter `mu`
+    pub fn new(r: Vector3, v: Vector3, mu: VType) -> Self {
+        let h = r * v;
+
+        Self {
+            energy: scalar_mul(v, v) / 2.0 - mu / vec_len(r),
+            angular_momentum: h,
+            eccentricity: (v * h) / mu - r / vec_len(r),
+        }
+    }
+
+    /// Drift of `self` from `init` invariants
+    pub fn drift(&self, init: &Invariants) -> Drift {
+        Drift {
+            energy: relative((self.energy - init.energy).abs(), init.energy),
+            angular_momentum: relative(
+                vec_len(self.angular_momentum - init.angular_momentum),
+                vec_len(init.angular_momentum),
+            ),
+            eccentricity: vec_len(self.eccentricity - init.eccentricity),
+        }
+    }
+}
+
+/// Iterator adapter which reports the drift of the invariants
+/// for every value of the wrapped solver
+///
+/// Should be created with `TwoBodySystem::monitor`
+pub struct Monitor<I, const N: usize> {
+    solver: I,
+    init: Invariants,
+    mu: VType,
+}
+
+impl<I, const N: usize> Monitor<I, N> {
+    pub fn new(solver: I, init: Invariants, mu: VType) -> Self {
+        Self { solver, init, mu }
+    }
+
+    /// Invariants of the init state
+    pub fn init(&self) -> Invariants {
+        self.init
+    }
+}
+
+impl<I, const N: usize> Iterator for Monitor<I, N>
+where
+    I: Iterator<Item = State<N>>,
+    Dim<N>: Space,
+{
+    type Item = (State<N>, Drift);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = self.solver.next()?;
+
+        let (r, v) = split_state::<N>(&state);
+        let drift = Invariants::new(r, v, self.mu).drift(&self.init);
+
+        Some((state, drift))
+    }
+}
+
+#[test]
+fn test_monitor() {
+    use crate::twobody::{Body3d, TwoBodySystem3d};
+
+    let system = TwoBodySystem3d::new(
+        Body3d {
+            m: 5.0,
+            pos: [0.0, 0.0, 0.0].into(),
+            velocity: [0.5, 0.0, 0.1].into(),
+        },
+        Body3d {
+            m: 5.0,
+            pos: [1.0, 1.0, 0.0].into(),
+            velocity: [-0.5, 0.0, 0.0].into(),
+        },
+        0.1,
+    );
+
+    let init = system.invariants(system.get_init());
+    assert!((init.energy - system.energy(system.get_init())).abs() < 1e-15);
+
+    let rk4: Vec<_> = system
+        .monitor(system.construct_rk4(0.01))
+        .take(2000)
+        .collect();
+    let heun: Vec<_> = system
+        .monitor(system.construct_heun(0.01))
+        .take(2000)
+        .collect();
+
+    let (_, rk4_drift) = rk4.last().unwrap();
+    let (_, heun_drift) = heun.last().unwrap();
+
+    assert!(rk4_drift.energy < 1e-6);
+    assert!(rk4_drift.angular_momentum < 1e-6);
+    assert!(rk4_drift.eccentricity < 1e-6);
+
+    assert!(heun_drift.energy > rk4_drift.energy);
+    assert!(heun_drift.eccentricity > rk4_drift.eccentricity);
+}
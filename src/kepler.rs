@@ -1,23 +1,29 @@
-use crate::twobody::TwoBodySystem2d;
-use crate::vector::{Vector3, Vector5};
-use std::f64::consts::{self, PI};
-
+use crate::anomaly::{self, Conic};
+use crate::elements::OrbitalElements;
+use crate::methods::{Solver, Stats};
+use crate::vector::{Vector3, Vector7};
+use std::error::Error;
+use std::fmt::Display;
+
+/// Analytic solver which propagates orbital elements
+/// with Kepler's equation
+///
+/// Iterator returns states `[t, x, y, z, vx, vy, vz]` of the orbit
+/// with a fixed time step
 #[derive(Debug)]
 pub struct Kepler {
     // Kepler's parameters
     
-    // Semi-major axis
-    a: f64,
-    // Eccentricity
-    e: f64,
-    // Argument of periapsis
-    w: f64,
-    // Longitute of ascending node (LAN)
-    omega: f64,
-    // Inclination
-    i: f64,
-    // Mean anomaly
-    M: f64,
+    // Orbital elements at `t0`
+    elements: OrbitalElements,
+    // Mean anomaly at `t0`
+    //
+    // Elliptic orbit: M = E - e * sin(E)
+    // Hyperbolic orbit: M = e * sinh(H) - H
+    // Parabolic orbit: M = D + D^3 / 3, D = tan(nu / 2) (Barker's equation)
+    m0: f64,
+    // Mean motion: M(t) = m0 + n * (t - t0)
+    n: f64,
     // std gravitational parameter
     mu: f64,
 
@@ -28,169 +34,203 @@ pub struct Kepler {
     // Current time
     t: f64,
     // Time step
-    step: f64
+    step: f64,
+    // Max length of the next step
+    clip: f64,
+    stats: Stats
 }
 
-fn vec_len(vec: Vector3) -> f64 {
-    (vec[0].powi(2) + vec[1].powi(2) + vec[2].powi(2)).sqrt()
+/// Errors of Kepler's parameters calculation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeplerError {
+    /// Standard gravitational parameter is not positive
+    NonPositiveMu(f64),
+    /// Angular momentum is zero: position or velocity is zero
+    /// or they are collinear (radial motion)
+    Degenerate,
+    /// Position is zero: the bodies are in the same point
+    ZeroRadius,
 }
 
-fn scalar_mul(v1: Vector3, v2: Vector3) -> f64 {
-    v1[0] * v2[0] + v1[1] * v2[1] + v1[2] * v2[2]
+impl Display for KeplerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeplerError::NonPositiveMu(mu) => {
+                write!(f, "gravitational parameter must be positive, got {}", mu)
+            }
+            KeplerError::Degenerate => write!(f, "angular momentum is zero"),
+            KeplerError::ZeroRadius => write!(f, "bodies are in the same point"),
+        }
+    }
 }
 
-fn angle_between(v1: Vector3, v2: Vector3) -> f64 {
-    scalar_mul(v1, v2) / (vec_len(v1) * vec_len(v2))
-}
+impl Error for KeplerError {}
 
 impl Kepler {
-    pub fn new(r: Vector3, v: Vector3, mu: f64, step: f64) -> Self {
-
-        dbg!(r, v);
-        // 1. a) Calculate orbital momentum vector h
-        let h = r * v;
-
-        dbg!(h);
-
-        // 1. b) Obtain the eccentricity vector e
-        let e_vec = (v * h) / mu - r / vec_len(r);
+    /// Calculate Kepler's parameters of the orbit from position `r`
+    /// and velocity `v` with std gravitational parameter `mu`
+    ///
+    /// Elliptic, parabolic and hyperbolic orbits are supported
+    ///
+    /// Iterator returns values with `step` time step
+    pub fn new(r: Vector3, v: Vector3, mu: f64, step: f64) -> Result<Self, KeplerError> {
+        let elements = OrbitalElements::from_state(r, v, mu)?;
+
+        Self::from_elements(elements, mu, step)
+    }
 
-        dbg!(e_vec);
+    /// Create solver for the orbit with `elements` with
+    /// std gravitational parameter `mu`
+    ///
+    /// Iterator returns values with `step` time step
+    pub fn from_elements(elements: OrbitalElements, mu: f64, step: f64) -> Result<Self, KeplerError> {
+        if mu <= 0.0 || mu.is_nan() {
+            return Err(KeplerError::NonPositiveMu(mu));
+        }
 
-        // 1. c) Determine the vector n pointing towards the asscending
-        // node and the true anomaly nu
-        let n: Vector3 = [-h[1], h[0], 0.0].into();
+        let OrbitalElements { p, e, nu, .. } = elements;
 
-        dbg!(n);
+        // 1. Compute the semi-major axis a
+        let a = elements.semi_major_axis();
 
-        let nu = if scalar_mul(r, v) >= 0.0 {
-            angle_between(e_vec, r).acos()
-        } else {
-            2.0 * PI - angle_between(e_vec, r).acos()
+        // 2. Compute the mean anomaly M and the mean motion n
+        let m0 = anomaly::true_to_mean(nu, e);
+        let n = match Conic::from_eccentricity(e) {
+            Conic::Elliptic => (mu / a.powi(3)).sqrt(),
+            Conic::Hyperbolic => (mu / (-a).powi(3)).sqrt(),
+            Conic::Parabolic => 2.0 * (mu / p.powi(3)).sqrt(),
         };
 
+        Ok(Self { elements, m0, n, mu, t0: 0.0, t: 0.0 + step, step, clip: f64::INFINITY, stats: Stats::default() })
+    }
 
-        dbg!(nu);
-
-        // 2. Calculate the orbit inclination i
-        let i = (h[2] / vec_len(h)).acos();
-
-        dbg!(i);
+    /// Orbital elements at the init time
+    pub fn elements(&self) -> OrbitalElements {
+        self.elements
+    }
 
-        // 3. Determine the orbit eccentricity e and the eccentric anomaly E
-        let e = vec_len(e_vec);
-        assert!(e < 1.0);
-        let E = 2.0 * ((nu / 2.0).tan() / ((1.0 + e) / (1.0 - e)).sqrt()).atan();
+    /// Set time of the next value, the following values
+    /// are returned with `step` after it
+    pub fn set_current_time(&mut self, t: f64) {
+        self.t = t;
+    }
 
-        dbg!(e);
-        dbg!(E);
+    // Return Vector7 = [t, x, y, z, vx, vy, vz] at `t` time
+    fn state(&self, t: f64) -> Vector7 {
+        // 1. Calculate Mt
+        // i. Determine the time difference
+        let delta_t = t - self.t0;
+        // ii. Calculate mean anomaly Mt
+        let mt = self.m0 + delta_t * self.n;
 
-        // 4. Obtain the longitute of ascending node omega
-        // and the argument of periapsis w
+        // 2. Solve Kepler's Equation for Mt and obtain the true anomaly nut
+        let nut = anomaly::mean_to_true(mt, self.elements.e).anomaly;
 
-        dbg!(i);
+        // 3. Obtain the position and velocity
+        let (r, v) = OrbitalElements { nu: nut, ..self.elements }.to_state(self.mu);
 
-        dbg!(i == PI);
+        [t, r[0], r[1], r[2], v[0], v[1], v[2]].into()
+    }
+}
 
-        let omega = if i.abs() < 0.0001 || i == PI {
-            0.0
-        } else {
-            let res = (n[0] / vec_len(n)).acos();
-            if n[1] < 0.0 {
-                2.0 * PI - res
-            } else {
-                res
-            }
-        };
+impl Iterator for Kepler {
+    type Item = Vector7;
 
-        dbg!(omega);
-
-        let w = if e.abs() < 0.0001 {
-            0.0
-        } else {
-            let res = if i.abs() < 0.0001 || i == PI {
-                e_vec[1].atan2(e_vec[0])
-            } else {
-                angle_between(n, e_vec).acos()
-            };
-            if e_vec[2] < 0.0 || i == PI {
-                2.0 * PI - res
-            } else {
-                res
-            }
-        };
+    // Return Vector7 = [t, x, y, z, vx, vy, vz]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Current time is the time of the next value
+        let t = if self.clip < self.step { self.t - self.step + self.clip } else { self.t };
+        let state = self.state(t);
 
-        dbg!(w);
+        self.t = t + self.step;
+        self.clip = f64::INFINITY;
+        self.stats.accepted += 1;
         
-        // 5. Compute the mean anomaly M
-        let M = E - e * E.sin();
-
-        dbg!(M);
+        Some ( state )
+    }
+}
 
-        // 6. Compute the semi-major axis a
-        let a = 1.0 / ((2.0 / vec_len(r)) - ((vec_len(v).powi(2) / mu)));
+impl Solver for Kepler {
+    fn stats(&self) -> Stats {
+        self.stats
+    }
 
-        dbg!(a);
+    fn current(&self) -> Vector7 {
+        self.state(self.t - self.step)
+    }
 
-        dbg!(step);
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
 
-        Self { a, e, w, omega, i, M, mu, t0: 0.0, t: 0.0 + step, step }
+    /// Exact solution at any `t` time
+    fn dense(&self, t: f64) -> Option<Vector7> {
+        Some(self.state(t))
     }
 
-    pub fn set_current_time(&mut self, t: f64) {
-        self.t = t;
+    fn has_dense(&self) -> bool {
+        true
     }
 }
 
-impl Iterator for Kepler {
-    type Item = Vector5;
+#[test]
+fn test_kepler_errors() {
+    let r = Vector3 { data: [1.0, 0.0, 0.0] };
 
-    // Return Vector5 = [t, x, y, z, 0]
-    // Last component is always zero
-    fn next(&mut self) -> Option<Self::Item> {
-        // 1. Calculate Mt
-        // i. Determine the time difference
-        let delta_t = self.t - self.t0;
-        // ii. Calculate mean anomaly Mt
-        let Mt = self.M + delta_t * (self.mu / self.a.powi(3)).sqrt();
+    let err = Kepler::new(r, [0.0, 1.0, 0.0].into(), 0.0, 0.1).unwrap_err();
+    assert_eq!(err, KeplerError::NonPositiveMu(0.0));
 
+    let err = Kepler::new(r, [2.0, 0.0, 0.0].into(), 1.0, 0.1).unwrap_err();
+    assert_eq!(err, KeplerError::Degenerate);
 
-        // 2. Solve Kepler's Equation: Mt = Et - esinE using Newton's method
-        let mut E = Mt;
-        let mut F = E - self.e * E.sin() - Mt;
+    assert!(Kepler::new(r, [0.0, 1.2, 0.0].into(), 1.0, 0.1).is_ok());
+}
 
-        let max_iter = 30;
-        let delta = 0.00000001;
+#[test]
+fn test_kepler_unbound_orbits() {
+    use crate::elements::vec_len;
 
-        for i in 0..max_iter {
-            E = E - F / (1.0 - self.e * E.cos());
-            F = E - self.e * E.sin() - Mt;
-            if F.abs() < delta { break; }
-        }
+    let r = Vector3 { data: [1.0, 0.0, 0.0] };
 
+    for &speed in &[2.0f64.sqrt(), 2.0] {
+        let v: Vector3 = [0.0, speed, 0.0].into();
+        let energy = speed * speed / 2.0 - 1.0;
 
-        // 3. Obtain the true anomaly nut
-        let nut = 2.0 * ((1.0 + self.e).sqrt() * (E / 2.0).sin()).atan2((1.0 - self.e).sqrt() * (E / 2.0).cos());
+        let kepler = Kepler::new(r, v, 1.0, 0.1).unwrap();
 
-        // 4. Use the eccentric anomaly to get the distance to the central body with
-        let rc = self.a * (1.0 - self.e * E.cos());
+        let mut last = Vector7::new();
+        for value in kepler.take(100) {
+            let pos: Vector3 = [value[1], value[2], value[3]].into();
+            let vel: Vector3 = [value[4], value[5], value[6]].into();
 
-        // 5. Obtain the position vector ot
-        let ot = Vector3 { data: [nut.cos(), nut.sin(), 0.0] } * rc;
+            // Trajectory must keep energy and angular momentum
+            assert!((vec_len(vel).powi(2) / 2.0 - 1.0 / vec_len(pos) - energy).abs() < 1e-9);
+            assert!(vec_len(pos * vel - r * v) < 1e-9);
 
-        // 6. Transform ot to the rectangular coordiantes r
-        
-        let x = ot[0] * (self.w.cos() * self.omega.cos() - self.w.sin() * self.i.cos() * self.omega.sin())
-            - ot[1] * (self.w.sin() * self.omega.cos() + self.w.cos() * self.i.cos() * self.omega.sin());
+            last = value;
+        }
 
-        let y = ot[0] * (self.w.cos() * self.omega.sin() + self.w.sin() * self.i.cos() * self.omega.cos())
-            + ot[1] * (self.w.cos() * self.i.cos() * self.omega.cos() - self.w.sin() * self.omega.sin());
+        // Body is going away
+        assert!(vec_len([last[1], last[2], last[3]].into()) > 3.0);
+    }
+}
 
-        let z = ot[0] * (self.w.sin() * self.i.sin()) + ot[1] * (self.w.cos() * self.i.sin());
+#[test]
+fn test_kepler_matches_universal() {
+    use crate::universal::Universal;
 
-        self.t += self.step;
-        
-        Some ( [(self.t - self.step), x, y, z, 0.0].into() )
+    let r: Vector3 = [1.0, 0.2, -0.3].into();
+
+    for &speed in &[0.8, 2.0f64.sqrt(), 1.8] {
+        let v: Vector3 = [0.1, speed, 0.4].into();
+
+        let kepler = Kepler::new(r, v, 1.0, 0.05).unwrap();
+        let universal = Universal::new(r, v, 1.0, 0.05).unwrap();
+
+        for (k, u) in kepler.zip(universal).take(200) {
+            for i in 0..7 {
+                assert!((k[i] - u[i]).abs() < 1e-6, "{} != {}", k, u);
+            }
+        }
     }
 }
-
@@ -0,0 +1,59 @@
+//! Solvers for the two-body problem
+//!
+//! The problem is reduced to the motion of the vector `r` between
+//! the two bodies, which is integrated either numerically with one of
+//! the [`methods`] or analytically with [`kepler::Kepler`] and
+//! [`universal::Universal`].
+//!
+//! # Examples
+//!
+//! ```
+//! use two_body::{Body2d, TwoBodySystem2d};
+//!
+//! let body1 = Body2d {
+//!     m: 5.0,
+//!     pos: [0.0, 0.0].into(),
+//!     velocity: [0.5, 0.0].into(),
+//! };
+//!
+//! let body2 = Body2d {
+//!     m: 5.0,
+//!     pos: [1.0, 1.0].into(),
+//!     velocity: [-0.5, 0.0].into(),
+//! };
+//!
+//! let system = TwoBodySystem2d::new(body1, body2, 0.1);
+//! let reader = system.build_reader();
+//!
+//! for step in system.construct_rk4(0.01).take(10) {
+//!     let position = reader.get(step);
+//!     println!("{}", position);
+//! }
+//! ```
+
+pub mod anomaly;
+pub mod elements;
+pub mod invariants;
+pub mod kepler;
+pub mod methods;
+pub mod soe;
+pub mod twobody;
+pub mod universal;
+pub mod vector;
+
+pub use elements::OrbitalElements;
+pub use invariants::{Drift, Invariants, Monitor};
+pub use kepler::{Kepler, KeplerError};
+pub use methods::{
+    events::{Direction, Event, EventRecord, Events},
+    output::{grid, Output},
+    terminate::Terminate,
+    Adaptive, Control, Solver, Starter, Stats, StepError, StepLimits, StepRecord, Tolerance,
+};
+pub use soe::Soe;
+pub use twobody::{
+    Body, Body2d, Body3d, Dim, Position, Space, State, TwoBodyReader, TwoBodyReader2d,
+    TwoBodyReader3d, TwoBodySystem, TwoBodySystem2d, TwoBodySystem3d, VType,
+};
+pub use universal::Universal;
+pub use vector::Vector;
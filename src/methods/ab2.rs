@@ -5,24 +5,41 @@ use std::{
     ops::{Add, Div, Mul, Sub},
 };
 
-use super::call_soe;
+use super::{call_soe, Solver, Starter, Stats};
 
+/// Adams–Bashforth 2nd order method
+///
+/// The first step is made by `Starter`
 pub struct Ab2<T, S> {
-    init1: T,
-    init2: T,
+    init: T,
+    // Derivative in the previous point multiplied by `h`
+    prev: Option<T>,
     soe: S,
     h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    starter: Starter,
+    stats: Stats,
 }
 
 impl<T, S> Ab2<T, S> {
-    pub fn new(init1: T, init2: T, soe: S, h: f64) -> Self {
+    pub fn new(init: T, soe: S, h: f64) -> Self {
         Self {
-            init1,
-            init2,
+            init,
+            prev: None,
             soe,
             h,
+            clip: f64::INFINITY,
+            starter: Starter::default(),
+            stats: Stats::default(),
         }
     }
+
+    /// Set method of the first step, `Starter::Rk4` by default
+    pub fn starter(mut self, starter: Starter) -> Self {
+        self.starter = starter;
+        self
+    }
 }
 
 impl<T, S> Iterator for Ab2<T, S>
@@ -40,14 +57,104 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result2 = call_soe(&mut self.soe, &self.init2) * self.h;
-        let result1 = call_soe(&mut self.soe, &self.init1) * self.h;
+        let t = self.init.as_ref()[0];
+
+        let f = call_soe(&mut self.soe, &self.init, &mut self.stats);
+
+        if self.clip < self.h {
+            // Clipped step breaks the equal spacing, so the method restarts
+            let mut next =
+                self.starter
+                    .step(&mut self.soe, self.init, f, self.clip, &mut self.stats);
+            next.as_mut()[0] = t + self.clip;
+
+            self.clip = f64::INFINITY;
+            self.prev = None;
+            self.init = next;
+
+            self.stats.accepted += 1;
+
+            return Some(next);
+        }
+
+        self.clip = f64::INFINITY;
+
+        let result2 = f * self.h;
 
-        self.init1 = self.init2;
+        let next = match self.prev {
+            Some(result1) => {
+                let mut next = self.init + result2 * 3.0 / 2.0 - result1 / 2.0;
+                next.as_mut()[0] = t + self.h;
+                next
+            }
+            None => self
+                .starter
+                .step(&mut self.soe, self.init, f, self.h, &mut self.stats),
+        };
 
-        self.init2 = self.init1 + result2 * 3.0 / 2.0 - result1 / 2.0;
-        self.init2.as_mut()[0] = self.init1.as_ref()[0] + self.h;
+        self.prev = Some(result2);
+        self.init = next;
 
-        Some(self.init2)
+        self.stats.accepted += 1;
+
+        Some(next)
     }
 }
+
+impl<T, S> Solver for Ab2<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Div<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_ab2() {
+    use crate::twobody::{observed_order, test_system};
+
+    let system = test_system();
+
+    // The order doesn't depend on the starter
+    for &starter in &[Starter::Heun, Starter::Rk4] {
+        let ab2 = |h| system.construct_ab2(h).starter(starter);
+        let order = observed_order(&system, ab2, 0.01, 1.0);
+        assert!((order - 2.0).abs() < 0.2, "{}", order);
+    }
+
+    let mut ab2 = system.construct_ab2(0.01);
+    let first = ab2.next().unwrap();
+    assert!((first[0] - 0.01).abs() < 1e-15);
+    ab2.by_ref().take(9).count();
+    assert_eq!(
+        ab2.stats(),
+        Stats {
+            rhs_calls: 4 + 9,
+            accepted: 10,
+            rejected: 0
+        }
+    );
+
+    // Clipped step restarts with the derivative already computed
+    ab2.clip(0.005);
+    let clipped = ab2.next().unwrap();
+    assert!((clipped[0] - 0.105).abs() < 1e-15);
+    assert_eq!(ab2.stats().rhs_calls, 4 + 9 + 4);
+}
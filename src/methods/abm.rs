@@ -0,0 +1,532 @@
+use super::{
+    call_soe,
+    controller::{Controller, Integral},
+    lagrange_weights, next_step_size, Adaptive, Control, Solver, Starter, Stats, StepRecord,
+    Tolerance,
+};
+use crate::soe::Soe;
+
+use std::collections::VecDeque;
+use std::iter::Iterator;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Max order of `Abm`
+pub const MAX_ORDER: usize = 12;
+
+/// Coefficients of `k`-step Adams–Bashforth method:
+/// `y[n+1] = y[n] + h * sum(b[j] * f[n-j])`
+pub fn adams_bashforth(k: usize) -> Vec<f64> {
+    let nodes: Vec<_> = (0..k).map(|j| -(j as f64)).collect();
+
+    lagrange_weights(&nodes, 1.0)
+}
+
+/// Coefficients of Adams–Moulton method of order `k`:
+/// `y[n+1] = y[n] + h * sum(b[j] * f[n+1-j])`
+pub fn adams_moulton(k: usize) -> Vec<f64> {
+    let nodes: Vec<_> = (0..k).map(|j| 1.0 - j as f64).collect();
+
+    lagrange_weights(&nodes, 1.0)
+}
+
+/// Error constants of Adams–Moulton methods: method of order `q`
+/// has local error `gamma[q] * h^(q+1) * y^(q+1)`
+fn moulton_gamma(n: usize) -> Vec<f64> {
+    let mut gamma = vec![1.0];
+
+    for j in 1..n {
+        let sum: f64 = (0..j).map(|i| gamma[i] / (j + 1 - i) as f64).sum();
+        gamma.push(-sum);
+    }
+
+    gamma
+}
+
+/// Adams–Bashforth–Moulton method in PECE form
+///
+/// Predictor is `k`-step Adams–Bashforth method, corrector is
+/// Adams–Moulton method of the same order `k`. With fixed step
+/// the first `k - 1` steps are made by `Starter`.
+///
+/// In variable mode (see `variable`) both step size and order are
+/// chosen from local error estimates, the method starts with order 1.
+/// Step size factors of the candidate orders are found with copies
+/// of the controller, only the chosen order updates its state
+pub struct Abm<T, S, C = Integral> {
+    init: T,
+    soe: S,
+    h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    // Current order and the max order
+    order: usize,
+    max_order: usize,
+    // Times and derivatives of the last points, the newest first
+    times: VecDeque<f64>,
+    history: VecDeque<T>,
+    // Coefficients of the fixed step mode
+    bashforth: Vec<f64>,
+    moulton: Vec<f64>,
+    gamma: Vec<f64>,
+    starter: Starter,
+    // Tolerance of the variable mode
+    tol: Option<Tolerance>,
+    max: f64,
+    control: Control,
+    controller: C,
+}
+
+impl<T, S> Abm<T, S> {
+    /// Create method of `order` with fixed `h` step
+    pub fn new(init: T, soe: S, h: f64, order: usize) -> Self {
+        assert!(
+            (1..=MAX_ORDER).contains(&order),
+            "order must be from 1 to {}",
+            MAX_ORDER
+        );
+
+        Self {
+            init,
+            soe,
+            h,
+            clip: f64::INFINITY,
+            order,
+            max_order: order,
+            times: VecDeque::new(),
+            history: VecDeque::new(),
+            bashforth: adams_bashforth(order),
+            moulton: adams_moulton(order),
+            gamma: moulton_gamma(MAX_ORDER + 2),
+            starter: Starter::default(),
+            tol: None,
+            max: f64::INFINITY,
+            control: Control::default(),
+            controller: Integral::default(),
+        }
+    }
+}
+
+impl<T, S, C> Abm<T, S, C> {
+    /// Set step size controller of the variable mode, `Integral` by default
+    pub fn controller<C2: Controller + Clone>(self, controller: C2) -> Abm<T, S, C2> {
+        Abm {
+            init: self.init,
+            soe: self.soe,
+            h: self.h,
+            clip: self.clip,
+            order: self.order,
+            max_order: self.max_order,
+            times: self.times,
+            history: self.history,
+            bashforth: self.bashforth,
+            moulton: self.moulton,
+            gamma: self.gamma,
+            starter: self.starter,
+            tol: self.tol,
+            max: self.max,
+            control: self.control,
+            controller,
+        }
+    }
+
+    /// Set method of the first steps, `Starter::Rk4` by default
+    pub fn starter(mut self, starter: Starter) -> Self {
+        self.starter = starter;
+        self
+    }
+
+    /// Choose step size and order (up to the order of the method)
+    /// with `tol` tolerance, iterator stops after `max` time
+    pub fn variable(mut self, tol: impl Into<Tolerance>, max: f64) -> Self {
+        self.tol = Some(tol.into());
+        self.max = max;
+        self.order = 1;
+        self
+    }
+
+    /// Step size of the next step
+    pub fn h(&self) -> f64 {
+        self.h
+    }
+
+    /// Order of the next step
+    pub fn order(&self) -> usize {
+        self.order
+    }
+}
+
+/// Step size is controlled in variable mode only
+impl<T, S, C> Adaptive for Abm<T, S, C> {
+    fn control(&self) -> &Control {
+        &self.control
+    }
+
+    fn control_mut(&mut self) -> &mut Control {
+        &mut self.control
+    }
+}
+
+impl<T, S, C> Abm<T, S, C>
+where
+    C: Controller + Clone,
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Div<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    /// Sum of `history` derivatives with `coefs` starting from `first`
+    fn combine(&self, coefs: &[f64], first: Option<T>) -> T {
+        let mut result = T::default();
+        let mut derivs = first.iter().chain(self.history.iter());
+
+        for (&c, &f) in coefs.iter().zip(&mut derivs) {
+            result = result + f * c;
+        }
+
+        result
+    }
+
+    /// Keep derivative `f` at time `t` in the history
+    fn push(&mut self, t: f64, f: T) {
+        self.times.push_front(t);
+        self.history.push_front(f);
+
+        self.times.truncate(self.max_order + 1);
+        self.history.truncate(self.max_order + 1);
+    }
+
+    /// Estimate of the local error of the method of order `q`
+    /// for the step to `next` with derivative `f`
+    fn estimate(&self, q: usize, next: &T, f: T) -> f64 {
+        let t = next.as_ref()[0];
+
+        let mut times = vec![t];
+        times.extend(self.times.iter().take(q));
+        let mut values = vec![f];
+        values.extend(self.history.iter().take(q));
+
+        // Divided differences, values[q] is the difference of order q
+        for j in 1..=q {
+            for i in (j..=q).rev() {
+                values[i] = (values[i - 1] - values[i]) / (times[i - j] - times[i]);
+            }
+        }
+
+        let factorial: f64 = (1..=q).map(|i| i as f64).product();
+        let mut error = values[q] * (self.gamma[q].abs() * factorial * self.h.powi(q as i32 + 1));
+        error.as_mut()[0] = 0.0;
+
+        match &self.tol {
+            Some(tol) => tol.norm(&error, &self.init, next),
+            None => 0.0,
+        }
+    }
+
+    /// Make PECE step of order `k` with the current step size
+    fn pece(&mut self, k: usize) -> (T, T) {
+        let t = self.init.as_ref()[0];
+
+        // Coefficients for the last points which may be not equidistant
+        let variable = self.tol.as_ref().map(|_| {
+            let nodes: Vec<_> = self
+                .times
+                .iter()
+                .take(k)
+                .map(|&ti| (ti - t) / self.h)
+                .collect();
+            let mut corrector = vec![1.0];
+            corrector.extend(&nodes[..k - 1]);
+
+            (
+                lagrange_weights(&nodes, 1.0),
+                lagrange_weights(&corrector, 1.0),
+            )
+        });
+
+        let (bashforth, moulton) = match &variable {
+            Some((bashforth, moulton)) => (bashforth, moulton),
+            None => (&self.bashforth, &self.moulton),
+        };
+
+        let mut prediction = self.init + self.combine(bashforth, None) * self.h;
+        prediction.as_mut()[0] = t + self.h;
+
+        let f = call_soe(&mut self.soe, &prediction, &mut self.control.stats);
+
+        let mut next = self.init + self.combine(moulton, Some(f)) * self.h;
+        next.as_mut()[0] = t + self.h;
+
+        let f = call_soe(&mut self.soe, &next, &mut self.control.stats);
+
+        (next, f)
+    }
+
+    fn next_fixed(&mut self) -> T {
+        let t = self.init.as_ref()[0];
+        // Derivative in `init` is the newest in the history
+        let f0 = self.history[0];
+
+        if self.clip < self.h {
+            // Clipped step breaks the equal spacing, so the method restarts
+            let h = self.clip;
+
+            let mut next =
+                self.starter
+                    .step(&mut self.soe, self.init, f0, h, &mut self.control.stats);
+            next.as_mut()[0] = t + h;
+
+            let f = call_soe(&mut self.soe, &next, &mut self.control.stats);
+
+            self.clip = f64::INFINITY;
+            self.times.clear();
+            self.history.clear();
+            self.push(t + h, f);
+            self.init = next;
+
+            return next;
+        }
+
+        self.clip = f64::INFINITY;
+
+        let (next, f) = if self.history.len() < self.order {
+            let mut next = self.starter.step(
+                &mut self.soe,
+                self.init,
+                f0,
+                self.h,
+                &mut self.control.stats,
+            );
+            next.as_mut()[0] = t + self.h;
+
+            let f = call_soe(&mut self.soe, &next, &mut self.control.stats);
+
+            (next, f)
+        } else {
+            self.pece(self.order)
+        };
+
+        self.push(t + self.h, f);
+        self.init = next;
+
+        next
+    }
+
+    fn next_variable(&mut self) -> Option<T> {
+        let t = self.init.as_ref()[0];
+
+        let mut rejections = 0;
+
+        loop {
+            let proposed = self.control.step_size(t, self.h)?;
+            self.h = proposed.min(self.clip);
+
+            let h = self.h;
+            let k = self.order.min(self.history.len());
+            let (next, f) = self.pece(k);
+
+            let r = self.estimate(k, &next, f);
+
+            self.control.record(StepRecord {
+                t,
+                h,
+                error: r,
+                accepted: r <= 1.0,
+            });
+
+            if r <= 1.0 {
+                // Candidate orders with their error norms and step size factors
+                let factor = |r: f64, q: usize| self.controller.clone().factor(r, q as i32 + 1);
+                let mut best = (k, r, factor(r, k));
+
+                if k > 1 {
+                    let r = self.estimate(k - 1, &next, f);
+                    let candidate = factor(r, k - 1);
+
+                    if candidate > best.2 {
+                        best = (k - 1, r, candidate);
+                    }
+                }
+
+                if k < self.max_order && self.history.len() > k {
+                    let r = self.estimate(k + 1, &next, f);
+                    let candidate = factor(r, k + 1);
+
+                    if candidate > best.2 {
+                        best = (k + 1, r, candidate);
+                    }
+                }
+
+                let (order, r, _) = best;
+
+                self.order = order;
+
+                let factor = self.controller.factor(r, order as i32 + 1);
+                self.h = next_step_size(proposed, h, h * factor, true);
+
+                self.push(t + h, f);
+                self.init = next;
+                self.clip = f64::INFINITY;
+
+                return Some(next);
+            }
+
+            self.h *= self.controller.factor(r, k as i32 + 1);
+            self.order = k;
+
+            rejections += 1;
+            self.control.reject(t, rejections)?;
+        }
+    }
+}
+
+impl<T, S, C> Iterator for Abm<T, S, C>
+where
+    C: Controller + Clone,
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Div<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.init.as_ref()[0];
+
+        if t > self.max || self.control.error.is_some() {
+            return None;
+        }
+
+        if self.history.is_empty() {
+            let f = call_soe(&mut self.soe, &self.init, &mut self.control.stats);
+            self.push(t, f);
+        }
+
+        let next = match self.tol {
+            Some(_) => self.next_variable()?,
+            None => self.next_fixed(),
+        };
+
+        self.control.stats.accepted += 1;
+
+        Some(next)
+    }
+}
+
+impl<T, S, C> Solver for Abm<T, S, C>
+where
+    C: Controller + Clone,
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Div<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.control.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_coefficients() {
+    let close = |a: &[f64], b: &[f64]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-12)
+    };
+
+    assert!(close(&adams_bashforth(1), &[1.0]));
+    assert!(close(&adams_bashforth(2), &[1.5, -0.5]));
+    assert!(close(
+        &adams_bashforth(4),
+        &[55.0 / 24.0, -59.0 / 24.0, 37.0 / 24.0, -9.0 / 24.0]
+    ));
+    assert!(close(&adams_moulton(2), &[0.5, 0.5]));
+    assert!(close(
+        &adams_moulton(4),
+        &[9.0 / 24.0, 19.0 / 24.0, -5.0 / 24.0, 1.0 / 24.0]
+    ));
+
+    // Methods are exact for a constant derivative
+    for k in 1..=MAX_ORDER {
+        assert!((adams_bashforth(k).iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((adams_moulton(k).iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    assert!(close(
+        &moulton_gamma(4),
+        &[1.0, -0.5, -1.0 / 12.0, -1.0 / 24.0]
+    ));
+}
+
+#[test]
+fn test_abm() {
+    use crate::methods::controller::Pi;
+    use crate::twobody::{max_error, observed_order, test_system};
+
+    let system = test_system();
+
+    for order in 1..=6 {
+        let abm = |h| system.construct_abm(h, order);
+        let observed = observed_order(&system, abm, 0.02, 1.0);
+        assert!(
+            (observed - order as f64).abs() < 0.4,
+            "{} {}",
+            order,
+            observed
+        );
+    }
+
+    let mut dopri5 = system.construct_dopri5(0.001, 1e-10, 20.0);
+    dopri5.by_ref().count();
+
+    for &order in &[8, 12] {
+        let mut abm = system
+            .construct_abm(0.001, order)
+            .variable(1e-10, 20.0)
+            .record_steps();
+        let mut max_order = 0;
+
+        while let Some(state) = abm.next() {
+            assert!(max_error(&system, state) < 1e-6);
+            max_order = max_order.max(abm.order());
+        }
+
+        assert!(abm.error().is_none());
+        assert!(max_order > 4);
+        assert_eq!(
+            abm.records().len(),
+            abm.stats().accepted + abm.stats().rejected
+        );
+        assert!(abm.stats().rhs_calls < dopri5.stats().rhs_calls / 2);
+    }
+
+    // Any step size controller in variable mode
+    let mut abm = system
+        .construct_abm(0.001, 8)
+        .variable(1e-10, 20.0)
+        .controller(Pi::default());
+    for state in abm.by_ref() {
+        assert!(max_error(&system, state) < 1e-6);
+    }
+    assert!(abm.error().is_none());
+}
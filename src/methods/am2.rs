@@ -5,24 +5,42 @@ use std::{
     ops::{Add, Div, Mul, Sub},
 };
 
-use super::call_soe;
+use super::{call_soe, Solver, Starter, Stats};
 
+/// Adams–Moulton 2-step method in predictor-corrector form,
+/// the predictor is Adams–Bashforth 2nd order method
+///
+/// The first step is made by `Starter`
 pub struct Am2<T, S> {
-    init1: T,
-    init2: T,
+    init: T,
+    // Derivative in the previous point multiplied by `h`
+    prev: Option<T>,
     soe: S,
     h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    starter: Starter,
+    stats: Stats,
 }
 
 impl<T, S> Am2<T, S> {
-    pub fn new(init1: T, init2: T, soe: S, h: f64) -> Self {
+    pub fn new(init: T, soe: S, h: f64) -> Self {
         Self {
-            init1,
-            init2,
+            init,
+            prev: None,
             soe,
             h,
+            clip: f64::INFINITY,
+            starter: Starter::default(),
+            stats: Stats::default(),
         }
     }
+
+    /// Set method of the first step, `Starter::Rk4` by default
+    pub fn starter(mut self, starter: Starter) -> Self {
+        self.starter = starter;
+        self
+    }
 }
 
 impl<T, S> Iterator for Am2<T, S>
@@ -40,17 +58,94 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result2 = call_soe(&mut self.soe, &self.init2) * self.h;
-        let result1 = call_soe(&mut self.soe, &self.init1) * self.h;
+        let t = self.init.as_ref()[0];
+
+        let f = call_soe(&mut self.soe, &self.init, &mut self.stats);
+
+        if self.clip < self.h {
+            // Clipped step breaks the equal spacing, so the method restarts
+            let mut next =
+                self.starter
+                    .step(&mut self.soe, self.init, f, self.clip, &mut self.stats);
+            next.as_mut()[0] = t + self.clip;
+
+            self.clip = f64::INFINITY;
+            self.prev = None;
+            self.init = next;
+
+            self.stats.accepted += 1;
+
+            return Some(next);
+        }
+
+        self.clip = f64::INFINITY;
+
+        let result2 = f * self.h;
+
+        let next = match self.prev {
+            Some(result1) => {
+                let tmp = self.init + result2 * 3.0 / 2.0 - result1 / 2.0;
+
+                let value = call_soe(&mut self.soe, &tmp, &mut self.stats) * self.h;
+
+                let mut next =
+                    self.init + value * 5.0 / 12.0 + result2 * 2.0 / 3.0 - result1 / 12.0;
+                next.as_mut()[0] = t + self.h;
+                next
+            }
+            None => self
+                .starter
+                .step(&mut self.soe, self.init, f, self.h, &mut self.stats),
+        };
+
+        self.prev = Some(result2);
+        self.init = next;
+
+        self.stats.accepted += 1;
+
+        Some(next)
+    }
+}
 
-        let tmp = self.init2 + result2 * 3.0 / 2.0 - result1 / 2.0;
+impl<T, S> Solver for Am2<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Div<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.stats
+    }
 
-        let value = call_soe(&mut self.soe, &tmp) * self.h;
+    fn current(&self) -> T {
+        self.init
+    }
 
-        self.init1 = self.init2;
-        self.init2 = self.init1 + value * 5.0 / 12.0 + result2 * 2.0 / 3.0 - result1 / 12.0;
-        self.init2.as_mut()[0] = self.init1.as_ref()[0] + self.h;
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_am2() {
+    use crate::twobody::{observed_order, test_system};
 
-        Some(self.init2)
+    let system = test_system();
+
+    // The order doesn't depend on the starter
+    for &starter in &[Starter::Heun, Starter::Rk4] {
+        let am2 = |h| system.construct_am2(h).starter(starter);
+        let order = observed_order(&system, am2, 0.01, 1.0);
+        assert!((order - 3.0).abs() < 0.2, "{}", order);
     }
+
+    let mut am2 = system.construct_am2(0.01);
+    am2.by_ref().take(10).count();
+    assert_eq!(am2.stats().rhs_calls, 4 + 9 * 2);
 }
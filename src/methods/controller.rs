@@ -0,0 +1,186 @@
+/// Max growth of the step size by default
+const MAX_FACTOR: f64 = 5.0;
+
+/// Max shrink of the step size by default
+const MIN_FACTOR: f64 = 0.2;
+
+/// Safety factor of the step size by default
+const SAFETY: f64 = 0.9;
+
+/// Step size controller of adaptive methods
+pub trait Controller {
+    /// Factor of the step size after the step with error norm `r`
+    /// (step is accepted when `r <= 1`)
+    ///
+    /// `order` is the order of the error estimate, that is
+    /// local error is proportional to `h^order`
+    fn factor(&mut self, r: f64, order: i32) -> f64;
+}
+
+/// Clamp the step size `factor`, zero and not finite error
+/// norms `r` give the max and the min factors
+fn clamp(factor: f64, r: f64, min_factor: f64, max_factor: f64) -> f64 {
+    if r == 0.0 {
+        max_factor
+    } else if !r.is_finite() {
+        min_factor
+    } else {
+        factor.max(min_factor).min(max_factor)
+    }
+}
+
+/// Integral (elementary) controller:
+/// `factor = safety * r^(-1 / order)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Integral {
+    /// Safety factor, less than 1
+    pub safety: f64,
+    /// Min factor of the step size
+    pub min_factor: f64,
+    /// Max factor of the step size
+    pub max_factor: f64,
+}
+
+impl Default for Integral {
+    fn default() -> Self {
+        Self {
+            safety: SAFETY,
+            min_factor: MIN_FACTOR,
+            max_factor: MAX_FACTOR,
+        }
+    }
+}
+
+impl Controller for Integral {
+    fn factor(&mut self, r: f64, order: i32) -> f64 {
+        let factor = self.safety * r.powf(-1.0 / order as f64);
+
+        clamp(factor, r, self.min_factor, self.max_factor)
+    }
+}
+
+/// Gustafsson PI controller:
+/// `factor = safety * r^(-alpha / order) * r_prev^(beta / order)`
+/// where `r_prev` is the error norm of the previous accepted step
+///
+/// Rejected steps use the integral rule, and the step size
+/// doesn't grow right after a rejection. It smooths the step size
+/// sequence where the integral controller oscillates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pi {
+    /// Safety factor, less than 1
+    pub safety: f64,
+    /// Integral gain
+    pub alpha: f64,
+    /// Proportional gain
+    pub beta: f64,
+    /// Min factor of the step size
+    pub min_factor: f64,
+    /// Max factor of the step size
+    pub max_factor: f64,
+    // Error norm of the previous accepted step
+    r_prev: f64,
+    // Previous step is rejected
+    rejected: bool,
+}
+
+impl Default for Pi {
+    fn default() -> Self {
+        Self {
+            safety: SAFETY,
+            alpha: 0.7,
+            beta: 0.4,
+            min_factor: MIN_FACTOR,
+            max_factor: MAX_FACTOR,
+            r_prev: 1.0,
+            rejected: false,
+        }
+    }
+}
+
+impl Controller for Pi {
+    fn factor(&mut self, r: f64, order: i32) -> f64 {
+        let order = order as f64;
+
+        if r > 1.0 || !r.is_finite() {
+            self.rejected = true;
+
+            let factor = self.safety * r.powf(-1.0 / order);
+            return clamp(factor, r, self.min_factor, 1.0);
+        }
+
+        // Too small error norm would give too large step
+        let r = r.max(1e-4);
+
+        let factor =
+            self.safety * r.powf(-self.alpha / order) * self.r_prev.powf(self.beta / order);
+        let max_factor = if self.rejected { 1.0 } else { self.max_factor };
+
+        self.r_prev = r;
+        self.rejected = false;
+
+        clamp(factor, r, self.min_factor, max_factor)
+    }
+}
+
+#[test]
+fn test_controllers() {
+    let mut integral = Integral::default();
+
+    assert_eq!(integral.factor(0.0, 5), MAX_FACTOR);
+    assert_eq!(integral.factor(f64::NAN, 5), MIN_FACTOR);
+    assert_eq!(integral.factor(1e10, 5), MIN_FACTOR);
+    assert!((integral.factor(32.0, 5) - 0.45).abs() < 1e-12);
+
+    let mut pi = Pi::default();
+
+    // Rejected step shrinks the step like the integral controller
+    assert!((pi.factor(32.0, 5) - 0.45).abs() < 1e-12);
+    // No growth right after the rejection
+    assert_eq!(pi.factor(1e-3, 5), 1.0);
+    // Growth is slowed by the previous small error
+    let factor = pi.factor(1e-3, 5);
+    assert!(factor > 1.0 && factor < integral.factor(1e-3, 5));
+}
+
+#[test]
+fn test_pi_rejections() {
+    use crate::methods::Solver;
+    use crate::twobody::{Body2d, TwoBodySystem2d};
+
+    // Eccentric orbit, step size changes a lot near periapsis
+    let system = TwoBodySystem2d::new(
+        Body2d {
+            m: 5.0,
+            pos: [0.0, 0.0].into(),
+            velocity: [0.2, 0.0].into(),
+        },
+        Body2d {
+            m: 5.0,
+            pos: [1.0, 1.0].into(),
+            velocity: [-0.2, 0.0].into(),
+        },
+        0.1,
+    );
+
+    let mut integral = system.construct_rk45(0.01, 1e-4, 50.0);
+    integral.by_ref().count();
+
+    let mut pi = system
+        .construct_rk45(0.01, 1e-4, 50.0)
+        .controller(Pi::default());
+    pi.by_ref().count();
+
+    assert!(integral.stats().rejected > 0);
+    assert!(pi.stats().rejected < integral.stats().rejected * 3 / 4);
+
+    let mut dopri5 = system.construct_dopri5(0.01, 1e-4, 50.0);
+    dopri5.by_ref().count();
+
+    let mut pi = system
+        .construct_dopri5(0.01, 1e-4, 50.0)
+        .controller(Pi::default());
+    pi.by_ref().count();
+
+    assert!(pi.stats().rejected < dopri5.stats().rejected * 3 / 4);
+}
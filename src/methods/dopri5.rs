@@ -0,0 +1,281 @@
+use super::{
+    call_soe,
+    controller::{Controller, Integral},
+    explicit_rk::{combine, stages},
+    next_step_size,
+    tableau::DORMAND_PRINCE,
+    Adaptive, Control, Solver, Stats, StepRecord, Tolerance,
+};
+use crate::soe::Soe;
+use std::iter::Iterator;
+use std::ops::{Add, Mul, Sub};
+
+// Dense output (Hairer, Norsett, Wanner)
+const D1: f64 = -12715105075.0 / 11282082432.0;
+const D3: f64 = 87487479700.0 / 32700410799.0;
+const D4: f64 = -10690763975.0 / 1880347072.0;
+const D5: f64 = 701980252875.0 / 199316789632.0;
+const D6: f64 = -1453857185.0 / 822651844.0;
+const D7: f64 = 69997945.0 / 29380423.0;
+
+/// Dormand–Prince 5(4) method, see `tableau::DORMAND_PRINCE`
+///
+/// Step is made with 5th order solution, error is estimated
+/// with embedded 4th order one. The last stage is the first stage
+/// of the next step (FSAL), so accepted step costs 6 calls of `soe`.
+///
+/// Solution between the last two values is available with `dense`
+pub struct Dopri5<T, S, C = Integral> {
+    init: T,
+    soe: S,
+    h: f64,
+    tol: Tolerance,
+    max: f64,
+    // Derivative in `init` (the last stage of the previous step)
+    k1: Option<T>,
+    // Stages of the last step
+    k: Vec<T>,
+    // Coefficients of the dense output for the last step
+    dense: Option<[T; 5]>,
+    // Time and size of the last step
+    t_last: f64,
+    h_last: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    control: Control,
+    controller: C,
+}
+
+impl<T, S> Dopri5<T, S> {
+    pub fn new(init: T, soe: S, h: f64, tol: impl Into<Tolerance>, max: f64) -> Self {
+        Self {
+            init,
+            soe,
+            h,
+            tol: tol.into(),
+            max,
+            k1: None,
+            k: Vec::with_capacity(DORMAND_PRINCE.stages()),
+            dense: None,
+            t_last: 0.0,
+            h_last: 0.0,
+            clip: f64::INFINITY,
+            control: Control::default(),
+            controller: Integral::default(),
+        }
+    }
+}
+
+impl<T, S, C> Dopri5<T, S, C> {
+    /// Set step size controller, `Integral` by default
+    pub fn controller<C2: Controller>(self, controller: C2) -> Dopri5<T, S, C2> {
+        Dopri5 {
+            init: self.init,
+            soe: self.soe,
+            h: self.h,
+            tol: self.tol,
+            max: self.max,
+            k1: self.k1,
+            k: self.k,
+            dense: self.dense,
+            t_last: self.t_last,
+            h_last: self.h_last,
+            clip: self.clip,
+            control: self.control,
+            controller,
+        }
+    }
+
+    /// Step size of the next attempt
+    pub fn h(&self) -> f64 {
+        self.h
+    }
+}
+
+impl<T, S, C> Adaptive for Dopri5<T, S, C> {
+    fn control(&self) -> &Control {
+        &self.control
+    }
+
+    fn control_mut(&mut self) -> &mut Control {
+        &mut self.control
+    }
+}
+
+impl<T, S, C> Dopri5<T, S, C>
+where
+    T: Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    /// Solution at time `t` from the last step.
+    ///
+    /// `t` should be between the last two values returned by
+    /// iterator, the polynomial is 4th order accurate there.
+    /// Returns `None` before the first step.
+    pub fn dense(&self, t: f64) -> Option<T> {
+        let [r1, r2, r3, r4, r5] = self.dense?;
+
+        let theta = (t - self.t_last) / self.h_last;
+        let theta1 = 1.0 - theta;
+
+        let mut result = r1 + (r2 + (r3 + (r4 + r5 * theta1) * theta) * theta1) * theta;
+        result.as_mut()[0] = t;
+
+        Some(result)
+    }
+}
+
+impl<T, S, C> Iterator for Dopri5<T, S, C>
+where
+    C: Controller,
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.init.as_ref()[0];
+
+        if t > self.max || self.control.error.is_some() {
+            return None;
+        }
+
+        let k1 = match self.k1 {
+            Some(k1) => k1,
+            None => call_soe(&mut self.soe, &self.init, &mut self.control.stats),
+        };
+
+        let mut rejections = 0;
+
+        loop {
+            self.h = self.control.step_size(t, self.h)?;
+
+            let h = self.h.min(self.clip);
+            let y = self.init;
+
+            self.k.clear();
+            self.k.push(k1);
+            stages(
+                &mut self.soe,
+                &DORMAND_PRINCE,
+                y,
+                h,
+                &mut self.k,
+                &mut self.control.stats,
+            );
+
+            // The last stage is evaluated in the solution (FSAL)
+            let mut next = y + combine(DORMAND_PRINCE.b, &self.k) * h;
+            next.as_mut()[0] = t + h;
+
+            let embedded = DORMAND_PRINCE.embedded.unwrap();
+            let mut error = next - (y + combine(embedded, &self.k) * h);
+            error.as_mut()[0] = 0.0;
+
+            let r = self.tol.norm(&error, &y, &next);
+            let accepted = r <= 1.0;
+
+            let factor = self.controller.factor(r, DORMAND_PRINCE.order);
+            self.h = next_step_size(self.h, h, h * factor, accepted);
+
+            self.control.record(StepRecord {
+                t,
+                h,
+                error: r,
+                accepted,
+            });
+
+            if accepted {
+                self.control.stats.accepted += 1;
+
+                let (k1, k3, k4, k5, k6, k7) =
+                    (k1, self.k[2], self.k[3], self.k[4], self.k[5], self.k[6]);
+                let diff = next - y;
+                let bspl = k1 * h - diff;
+
+                self.dense = Some([
+                    y,
+                    diff,
+                    bspl,
+                    diff - k7 * h - bspl,
+                    (k1 * D1 + k3 * D3 + k4 * D4 + k5 * D5 + k6 * D6 + k7 * D7) * h,
+                ]);
+                self.t_last = t;
+                self.h_last = h;
+
+                self.init = next;
+                self.k1 = Some(k7);
+                self.clip = f64::INFINITY;
+
+                return Some(next);
+            }
+
+            rejections += 1;
+            self.control.reject(t, rejections)?;
+        }
+    }
+}
+
+impl<T, S, C> Solver for Dopri5<T, S, C>
+where
+    C: Controller,
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.control.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+
+    fn dense(&self, t: f64) -> Option<T> {
+        Dopri5::dense(self, t)
+    }
+
+    fn has_dense(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_dopri5_dense() {
+    use crate::twobody::{max_error, test_system};
+
+    let system = test_system();
+
+    let mut solver = system.construct_dopri5(0.01, 1e-9, 20.0);
+    let mut prev = system.get_init()[0];
+
+    while let Some(state) = solver.next() {
+        assert!(max_error(&system, state) < 1e-6);
+
+        for &theta in &[0.25, 0.5, 0.75] {
+            let t = prev + (state[0] - prev) * theta;
+            let dense = solver.dense(t).unwrap();
+
+            assert_eq!(dense[0], t);
+            assert!(max_error(&system, dense) < 1e-6);
+        }
+
+        prev = state[0];
+    }
+
+    assert!(prev > 20.0);
+}
@@ -2,24 +2,95 @@ use crate::soe::Soe;
 
 use std::{
     iter::Iterator,
-    ops::{Add, Div, Mul},
+    ops::{Add, Div, Mul, Sub},
 };
 
-use super::call_soe;
+use super::{call_soe, state_abs, Solver, Stats, StepError};
 
-pub struct Euler<T, S> {
+/// Explicit (forward) Euler method, 1st order
+pub struct ExplicitEuler<T, S> {
     init: T,
     soe: S,
     h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    stats: Stats,
 }
 
-impl<T, S> Euler<T, S> {
+impl<T, S> ExplicitEuler<T, S> {
     pub fn new(init: T, soe: S, h: f64) -> Self {
-        Self { init, soe, h }
+        Self {
+            init,
+            soe,
+            h,
+            clip: f64::INFINITY,
+            stats: Stats::default(),
+        }
     }
 }
 
-impl<T, S> Iterator for Euler<T, S>
+impl<T, S> Iterator for ExplicitEuler<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default + Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let h = self.h.min(self.clip);
+        self.clip = f64::INFINITY;
+
+        let result = call_soe(&mut self.soe, &self.init, &mut self.stats);
+
+        self.init = self.init + result * h;
+
+        self.stats.accepted += 1;
+
+        Some(self.init)
+    }
+}
+
+impl<T, S> Solver for ExplicitEuler<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default + Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+/// Heun's method (explicit trapezoid rule), 2nd order
+pub struct Heun<T, S> {
+    init: T,
+    soe: S,
+    h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    stats: Stats,
+}
+
+impl<T, S> Heun<T, S> {
+    pub fn new(init: T, soe: S, h: f64) -> Self {
+        Self {
+            init,
+            soe,
+            h,
+            clip: f64::INFINITY,
+            stats: Stats::default(),
+        }
+    }
+}
+
+impl<T, S> Iterator for Heun<T, S>
 where
     S: Soe<Args = T>,
     T: Default
@@ -32,14 +103,215 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result = call_soe(&mut self.soe, &self.init);
-
-        let tmp = self.init + result * self.h;
+        let h = self.h.min(self.clip);
+        self.clip = f64::INFINITY;
 
-        let prediction = call_soe(&mut self.soe, &tmp);
+        let result = call_soe(&mut self.soe, &self.init, &mut self.stats);
+        self.init = step(&mut self.soe, self.init, result, h, &mut self.stats);
 
-        self.init = self.init + (result + prediction) * self.h / 2.0;
+        self.stats.accepted += 1;
 
         Some(self.init)
     }
 }
+
+/// Make one step of Heun's method with `h` from `init`,
+/// `result` is the derivative in `init`
+pub(crate) fn step<T, S>(soe: &mut S, init: T, result: T, h: f64, stats: &mut Stats) -> T
+where
+    S: Soe<Args = T>,
+    T: Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T> + Div<f64, Output = T>,
+{
+    let tmp = init + result * h;
+
+    let prediction = call_soe(soe, &tmp, stats);
+
+    init + (result + prediction) * h / 2.0
+}
+
+impl<T, S> Solver for Heun<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Div<f64, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+/// Implicit (backward) Euler method, 1st order
+///
+/// Equation `y[n+1] = y[n] + h * f(y[n+1])` is solved by fixed-point
+/// iteration starting from the explicit Euler step. Iteration converges
+/// when `h` is less than the inverse Lipschitz constant of `f`
+pub struct ImplicitEuler<T, S> {
+    init: T,
+    soe: S,
+    h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    // Relative tolerance and max number of iterations
+    tol: f64,
+    max_iterations: usize,
+    error: Option<StepError>,
+    stats: Stats,
+}
+
+impl<T, S> ImplicitEuler<T, S> {
+    pub fn new(init: T, soe: S, h: f64) -> Self {
+        Self {
+            init,
+            soe,
+            h,
+            clip: f64::INFINITY,
+            tol: 1e-12,
+            max_iterations: 50,
+            error: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Set relative tolerance `tol` and max number of iterations
+    /// of the fixed-point solve, 1e-12 and 50 by default
+    pub fn iterations(mut self, tol: f64, max_iterations: usize) -> Self {
+        self.tol = tol;
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Error which stopped the method
+    pub fn error(&self) -> Option<StepError> {
+        self.error
+    }
+}
+
+impl<T, S> Iterator for ImplicitEuler<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let h = self.h.min(self.clip);
+
+        let mut next = self.init + call_soe(&mut self.soe, &self.init, &mut self.stats) * h;
+
+        for _ in 0..self.max_iterations {
+            let prev = next;
+
+            next = self.init + call_soe(&mut self.soe, &prev, &mut self.stats) * h;
+
+            // The time is left out, so convergence does not depend on it
+            if state_abs(&(next - prev)) <= self.tol * (1.0 + state_abs(&next)) {
+                self.init = next;
+                self.clip = f64::INFINITY;
+                self.stats.accepted += 1;
+
+                return Some(next);
+            }
+        }
+
+        self.error = Some(StepError::NoConvergence {
+            t: self.init.as_ref()[0],
+            iterations: self.max_iterations,
+        });
+
+        None
+    }
+}
+
+impl<T, S> Solver for ImplicitEuler<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_euler_family() {
+    use crate::twobody::{observed_order, test_system};
+
+    let system = test_system();
+
+    // Halving the step reduces the error by 2^order
+    let explicit = observed_order(&system, |h| system.construct_explicit_euler(h), 0.002, 1.0);
+    let heun = observed_order(&system, |h| system.construct_heun(h), 0.002, 1.0);
+    let implicit = observed_order(&system, |h| system.construct_implicit_euler(h), 0.002, 1.0);
+
+    assert!((explicit - 1.0).abs() < 0.15, "{}", explicit);
+    assert!((heun - 2.0).abs() < 0.15, "{}", heun);
+    assert!((implicit - 1.0).abs() < 0.15, "{}", implicit);
+
+    // Explicit method gains energy, implicit one loses it
+    let energy = |state| system.energy(state);
+    let e0 = energy(system.get_init());
+    assert!(energy(system.construct_explicit_euler(0.01).nth(999).unwrap()) > e0);
+    assert!(energy(system.construct_implicit_euler(0.01).nth(999).unwrap()) < e0);
+
+    let mut implicit = system.construct_implicit_euler(10.0).iterations(1e-12, 5);
+    assert!(implicit.next().is_none());
+    assert_eq!(
+        implicit.error(),
+        Some(StepError::NoConvergence {
+            t: 0.0,
+            iterations: 5
+        })
+    );
+
+    // Convergence does not depend on the init time
+    let solve = |t0: f64| {
+        let mut init = system.get_init();
+        init[0] = t0;
+
+        let mut implicit = ImplicitEuler::new(init, system.generate_soe(), 0.01);
+        let last = implicit.nth(999).unwrap();
+        (last, implicit.stats().rhs_calls)
+    };
+
+    let (last, calls) = solve(0.0);
+    let (far, far_calls) = solve(1e8);
+
+    assert_eq!(calls, far_calls);
+    assert!((1..5).all(|i| (last[i] - far[i]).abs() < 1e-12));
+}
@@ -0,0 +1,482 @@
+use super::{call_soe, hermite, Solver, Stats};
+use crate::soe::Soe;
+
+use std::iter::Iterator;
+use std::ops::{Add, Mul};
+
+/// Direction of the zero crossing of event function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// From negative to non-negative values
+    Rising,
+    /// From positive to non-positive values
+    Falling,
+    /// Both directions
+    #[default]
+    Any,
+}
+
+impl Direction {
+    fn matches(self, direction: Direction) -> bool {
+        self == Direction::Any || self == direction
+    }
+}
+
+/// Event which occurs when function of the state crosses zero
+pub struct Event<'a, T> {
+    f: Box<dyn FnMut(&T) -> f64 + 'a>,
+    direction: Direction,
+    terminal: bool,
+}
+
+impl<'a, T> Event<'a, T> {
+    /// Create event for zero crossings of `f` in any direction
+    pub fn new(f: impl FnMut(&T) -> f64 + 'a) -> Self {
+        Self {
+            f: Box::new(f),
+            direction: Direction::Any,
+            terminal: false,
+        }
+    }
+
+    /// Detect crossings only in `direction`
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Stop integration at the event
+    pub fn terminal(mut self) -> Self {
+        self.terminal = true;
+        self
+    }
+}
+
+/// Event which occurred during integration
+#[derive(Debug, Clone, Copy)]
+pub struct EventRecord<T> {
+    /// Index of the event in the list of `Events`
+    pub event: usize,
+    /// Time of the event
+    pub t: f64,
+    /// State at the event
+    pub state: T,
+    /// Direction of the crossing, `Rising` or `Falling`
+    pub direction: Direction,
+}
+
+/// Root of `f` between `a` and `b` by Brent's method,
+/// `fa` and `fb` are values of `f` in the ends and have
+/// different signs (or one of them is zero)
+fn brent(mut f: impl FnMut(f64) -> f64, mut a: f64, mut b: f64, mut fa: f64, mut fb: f64) -> f64 {
+    let (mut c, mut fc) = (b, fb);
+    let mut d = b - a;
+    let mut e = d;
+
+    for _ in 0..100 {
+        if (fb > 0.0) == (fc > 0.0) && fb != 0.0 {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+
+        let tol = 2.0 * f64::EPSILON * b.abs().max(1.0);
+        let m = (c - b) / 2.0;
+
+        if m.abs() <= tol || fb == 0.0 {
+            return b;
+        }
+
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            // Secant or inverse quadratic interpolation
+            let s = fb / fa;
+
+            let (mut p, mut q) = if a == c {
+                (2.0 * m * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * m * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+
+            if 2.0 * p < (3.0 * m * q - (tol * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = m;
+                e = d;
+            }
+        } else {
+            // Bisection
+            d = m;
+            e = d;
+        }
+
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol { d } else { tol.copysign(m) };
+        fb = f(b);
+    }
+
+    b
+}
+
+/// Iterator adapter which detects events between values
+/// of the wrapped solver
+///
+/// State between two values is taken from the dense output of
+/// the solver if it has one (see `Solver::has_dense`), otherwise
+/// it is found by cubic Hermite interpolation with derivatives
+/// from `soe` (one extra call per step). The time
+/// of the event is located by Brent's method. Events are kept
+/// in `occurred`, terminal event stops the iterator: its state
+/// is the last value.
+///
+/// Should be created with `TwoBodySystem::events`
+pub struct Events<'a, I, T, S> {
+    solver: I,
+    soe: S,
+    events: Vec<Event<'a, T>>,
+    // The last value, its derivative and values of event functions
+    last: T,
+    derivative: Option<T>,
+    values: Vec<f64>,
+    occurred: Vec<EventRecord<T>>,
+    stopped: bool,
+    stats: Stats,
+}
+
+impl<'a, I, T, S> Events<'a, I, T, S> {
+    /// Detect `events` for `solver` from its current value
+    pub fn new(solver: I, soe: S, mut events: Vec<Event<'a, T>>) -> Self
+    where
+        I: Solver<Item = T>,
+    {
+        let init = solver.current();
+        let values = events.iter_mut().map(|event| (event.f)(&init)).collect();
+
+        Self {
+            solver,
+            soe,
+            events,
+            last: init,
+            derivative: None,
+            values,
+            occurred: Vec::new(),
+            stopped: false,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Events which occurred so far in order of time
+    pub fn occurred(&self) -> &[EventRecord<T>] {
+        &self.occurred
+    }
+
+    /// Terminal event stopped the iterator
+    pub fn stopped(&self) -> bool {
+        self.stopped
+    }
+}
+
+impl<'a, I, T, S> Iterator for Events<'a, I, T, S>
+where
+    I: Solver<Item = T>,
+    S: Soe<Args = T>,
+    T: Copy + AsRef<[f64]> + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stopped {
+            return None;
+        }
+
+        let next = self.solver.next()?;
+
+        let y0 = self.last;
+        let t0 = y0.as_ref()[0];
+        let h = next.as_ref()[0] - t0;
+
+        // Derivatives for the interpolation if the solver has no dense output
+        let derivatives = if self.solver.has_dense() {
+            None
+        } else {
+            let f0 = match self.derivative.take() {
+                Some(f0) => f0,
+                None => call_soe(&mut self.soe, &y0, &mut self.stats),
+            };
+            let f1 = call_soe(&mut self.soe, &next, &mut self.stats);
+            Some((f0, f1))
+        };
+
+        let solver = &self.solver;
+        let interpolate = |theta: f64| {
+            let t = t0 + theta * h;
+            let mut state = match derivatives {
+                Some((f0, f1)) => hermite(y0, f0, next, f1, h, theta),
+                None => solver
+                    .dense(t)
+                    .expect("Dense output is available after a step"),
+            };
+            state.as_mut()[0] = t;
+            state
+        };
+
+        let mut found = Vec::new();
+
+        for (i, event) in self.events.iter_mut().enumerate() {
+            let g0 = self.values[i];
+            let g1 = (event.f)(&next);
+            self.values[i] = g1;
+
+            let direction = if g0 < 0.0 && g1 >= 0.0 {
+                Direction::Rising
+            } else if g0 > 0.0 && g1 <= 0.0 {
+                Direction::Falling
+            } else {
+                continue;
+            };
+
+            if !event.direction.matches(direction) {
+                continue;
+            }
+
+            let f = &mut event.f;
+            let theta = brent(|theta| f(&interpolate(theta)), 0.0, 1.0, g0, g1);
+            let state = interpolate(theta);
+
+            found.push((
+                event.terminal,
+                EventRecord {
+                    event: i,
+                    t: state.as_ref()[0],
+                    state,
+                    direction,
+                },
+            ));
+        }
+
+        found.sort_by(|(_, a), (_, b)| a.t.partial_cmp(&b.t).unwrap());
+
+        for (terminal, record) in found {
+            self.occurred.push(record);
+
+            if terminal {
+                self.stopped = true;
+                self.last = record.state;
+                return Some(record.state);
+            }
+        }
+
+        self.last = next;
+        self.derivative = derivatives.map(|(_, f1)| f1);
+
+        Some(next)
+    }
+}
+
+impl<'a, I, T, S> Solver for Events<'a, I, T, S>
+where
+    I: Solver<Item = T>,
+    S: Soe<Args = T>,
+    T: Copy + AsRef<[f64]> + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    /// Statistics of the wrapped solver, calls of `soe`
+    /// for the interpolation are added
+    fn stats(&self) -> Stats {
+        let mut stats = self.solver.stats();
+        stats.rhs_calls += self.stats.rhs_calls;
+        stats
+    }
+
+    fn current(&self) -> T {
+        self.last
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.solver.clip(h);
+    }
+
+    fn dense(&self, t: f64) -> Option<T> {
+        self.solver.dense(t)
+    }
+
+    fn has_dense(&self) -> bool {
+        self.solver.has_dense()
+    }
+}
+
+#[test]
+fn test_brent() {
+    let root = brent(|x| x * x - 2.0, 0.0, 2.0, -2.0, 2.0);
+    assert!((root - 2.0f64.sqrt()).abs() < 1e-15);
+
+    let root = brent(|x: f64| x.cos(), 0.0, 3.0, 1.0, 3.0f64.cos());
+    assert!((root - std::f64::consts::FRAC_PI_2).abs() < 1e-15);
+
+    let root = brent(|x| x - 1.0, 0.0, 1.0, -1.0, 0.0);
+    assert_eq!(root, 1.0);
+}
+
+#[test]
+fn test_events() {
+    use crate::elements::vec_len;
+    use crate::twobody::{split_state, test_system, State};
+    use std::f64::consts::PI;
+
+    let system = test_system();
+    let init = system.invariants(system.get_init());
+
+    let a = -system.mu() / (2.0 * init.energy);
+    let e = vec_len(init.eccentricity);
+    let period = 2.0 * PI * (a * a * a / system.mu()).sqrt();
+
+    // Apsides with period of the orbit for any solver
+    let check = |solver: &mut dyn Solver<Item = State<2>>| {
+        let mut events = system.events(solver, vec![system.periapsis(), system.apoapsis()]);
+        events
+            .by_ref()
+            .take_while(|state| state[0] < 3.0 * period)
+            .count();
+
+        let occurred = events.occurred();
+        assert!(occurred.len() >= 5);
+
+        for (i, record) in occurred.iter().enumerate() {
+            let (r, _) = split_state::<2>(&record.state);
+            let expected = match record.direction {
+                Direction::Rising => a * (1.0 - e),
+                _ => a * (1.0 + e),
+            };
+
+            assert!((vec_len(r) - expected).abs() < 1e-6);
+            assert_eq!(record.t, record.state[0]);
+
+            if i >= 2 {
+                assert_eq!(record.event, occurred[i - 2].event);
+                assert!((record.t - occurred[i - 2].t - period).abs() < 1e-6);
+            }
+        }
+    };
+
+    check(&mut system.construct_rk4(0.01));
+    check(&mut system.construct_dopri5(0.1, 1e-12, f64::INFINITY));
+    check(&mut system.construct_rkn64(0.1, 1e-12, f64::INFINITY));
+    check(&mut system.construct_abm(0.005, 10));
+
+    // Terminal event stops the solver at the event
+    let mut events = system.events(
+        system.construct_rk4(0.1),
+        vec![
+            system.distance(a * (1.0 + e) - 0.1),
+            system
+                .plane([1.0, 0.0, 0.0].into())
+                .direction(Direction::Falling)
+                .terminal(),
+        ],
+    );
+    let last = events.by_ref().last().unwrap();
+
+    assert!(events.stopped());
+    assert!(last[1].abs() < 1e-10);
+    assert_eq!(events.occurred().last().unwrap().event, 1);
+    assert!(events.occurred().iter().all(|record| record.t <= last[0]));
+
+    // Event functions may depend on time
+    let times = [0.55, 1.25];
+    let mut events = system.events(
+        system.construct_rk4(0.1),
+        times
+            .iter()
+            .map(|&t| Event::new(move |state: &State<2>| state[0] - t))
+            .collect(),
+    );
+    events.by_ref().take(20).count();
+
+    assert_eq!(events.occurred().len(), 2);
+    for (record, &t) in events.occurred().iter().zip(&times) {
+        assert!((record.t - t).abs() < 1e-14);
+        assert_eq!(record.direction, Direction::Rising);
+    }
+
+    let stats = events.stats();
+    assert_eq!(stats.rhs_calls, 4 * 20 + 21);
+
+    // Detection starts from the current value of the solver
+    let mut solver = system.construct_rk4(0.01);
+    solver.by_ref().take(30).count();
+    let mut events = system.events(solver, vec![Event::new(|state: &State<2>| state[0] - 0.2)]);
+    events.by_ref().take(30).count();
+
+    assert!(events.occurred().is_empty());
+}
+
+#[test]
+fn test_events_dense() {
+    use crate::anomaly::true_to_mean;
+    use crate::elements::{vec_len, OrbitalElements};
+    use crate::twobody::{split_state, test_system, State};
+    use std::f64::consts::PI;
+
+    let system = test_system();
+    let (r, v) = split_state::<2>(&system.get_init());
+    let elements = OrbitalElements::from_state(r, v, system.mu()).unwrap();
+
+    let a = elements.p / (1.0 - elements.e * elements.e);
+    let n = (system.mu() / (a * a * a)).sqrt();
+    let mean = true_to_mean(elements.nu, elements.e);
+    let t_periapsis = (2.0 * PI - mean).rem_euclid(2.0 * PI) / n;
+
+    // Event of the exact solution is at the exact time
+    let check = |solver: &mut dyn Solver<Item = State<2>>| {
+        let mut events = system.events(solver, vec![system.periapsis()]);
+        events
+            .by_ref()
+            .take_while(|state| state[0] < t_periapsis + 1.0)
+            .count();
+
+        let record = events.occurred()[0];
+        let (r, _) = split_state::<2>(&record.state);
+
+        assert!((record.t - t_periapsis).abs() < 1e-9);
+        assert!((vec_len(r) - a * (1.0 - elements.e)).abs() < 1e-9);
+        assert_eq!(events.stats().rhs_calls, 0);
+    };
+
+    check(&mut system.construct_kepler(0.5).unwrap());
+    check(&mut system.construct_universal(0.5).unwrap());
+
+    // No extra calls of `soe` with dense output of the solver
+    let mut solver = system.construct_dopri5(0.1, 1e-10, f64::INFINITY);
+    let mut events = system.events(&mut solver, vec![system.periapsis()]);
+    events
+        .by_ref()
+        .take_while(|state| state[0] < t_periapsis + 1.0)
+        .count();
+
+    let t = events.occurred()[0].t;
+    let stats = events.stats();
+    drop(events);
+
+    assert!((t - t_periapsis).abs() < 1e-7);
+    assert_eq!(stats, solver.stats());
+}
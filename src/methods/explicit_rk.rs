@@ -0,0 +1,327 @@
+use super::{
+    call_soe,
+    controller::{Controller, Integral},
+    next_step_size,
+    tableau::Tableau,
+    Adaptive, Control, Solver, Stats, StepRecord, Tolerance,
+};
+use crate::soe::Soe;
+use std::iter::Iterator;
+use std::ops::{Add, Mul, Sub};
+
+/// Sum of stages `k` with `weights`
+pub(crate) fn combine<T>(weights: &[f64], k: &[T]) -> T
+where
+    T: Default + Copy + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    let mut result = T::default();
+
+    for (&w, &k) in weights.iter().zip(k) {
+        if w != 0.0 {
+            result = result + k * w;
+        }
+    }
+
+    result
+}
+
+/// Evaluate stages of the step with `h` from `init`,
+/// `k` must contain only the first stage
+pub(crate) fn stages<T, S>(
+    soe: &mut S,
+    tableau: &Tableau,
+    init: T,
+    h: f64,
+    k: &mut Vec<T>,
+    stats: &mut Stats,
+) where
+    S: Soe<Args = T>,
+    T: Default + Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    for row in tableau.a {
+        let args = init + combine(row, k) * h;
+        k.push(call_soe(soe, &args, stats));
+    }
+}
+
+/// Make one step of the method with `tableau` with `h` from `init`,
+/// `k1` is the derivative in `init`
+pub(crate) fn step<T, S>(
+    soe: &mut S,
+    tableau: &Tableau,
+    init: T,
+    k1: T,
+    h: f64,
+    stats: &mut Stats,
+) -> T
+where
+    S: Soe<Args = T>,
+    T: Default + Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    let mut k = vec![k1];
+    stages(soe, tableau, init, h, &mut k, stats);
+
+    init + combine(tableau.b, &k) * h
+}
+
+/// Explicit Runge–Kutta method with Butcher `Tableau`
+///
+/// Step is fixed by default. Tableau with embedded solution
+/// can be used with step size control, see `adaptive`.
+/// The last stage of FSAL tableau is reused in the next step
+pub struct ExplicitRk<T, S, C = Integral> {
+    init: T,
+    soe: S,
+    h: f64,
+    tableau: Tableau,
+    // Stages of the last step
+    k: Vec<T>,
+    // The first stage of the next step for FSAL tableau
+    fsal: Option<T>,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    // Tolerance of the adaptive mode
+    tol: Option<Tolerance>,
+    max: f64,
+    control: Control,
+    controller: C,
+}
+
+impl<T, S> ExplicitRk<T, S> {
+    /// Create method with `tableau` and fixed `h` step
+    pub fn new(init: T, soe: S, h: f64, tableau: Tableau) -> Self {
+        Self {
+            init,
+            soe,
+            h,
+            tableau,
+            k: Vec::with_capacity(tableau.stages()),
+            fsal: None,
+            clip: f64::INFINITY,
+            tol: None,
+            max: f64::INFINITY,
+            control: Control::default(),
+            controller: Integral::default(),
+        }
+    }
+}
+
+impl<T, S, C> ExplicitRk<T, S, C> {
+    /// Control step size with `tol` tolerance using the embedded
+    /// solution, iterator stops after `max` time
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tableau has no embedded solution
+    pub fn adaptive(mut self, tol: impl Into<Tolerance>, max: f64) -> Self {
+        assert!(
+            self.tableau.embedded.is_some(),
+            "tableau has no embedded solution"
+        );
+
+        self.tol = Some(tol.into());
+        self.max = max;
+        self
+    }
+
+    /// Set step size controller, `Integral` by default
+    pub fn controller<C2: Controller>(self, controller: C2) -> ExplicitRk<T, S, C2> {
+        ExplicitRk {
+            init: self.init,
+            soe: self.soe,
+            h: self.h,
+            tableau: self.tableau,
+            k: self.k,
+            fsal: self.fsal,
+            clip: self.clip,
+            tol: self.tol,
+            max: self.max,
+            control: self.control,
+            controller,
+        }
+    }
+
+    /// Step size of the next attempt
+    pub fn h(&self) -> f64 {
+        self.h
+    }
+
+    /// Butcher tableau of the method
+    pub fn tableau(&self) -> &Tableau {
+        &self.tableau
+    }
+}
+
+impl<T, S, C> Adaptive for ExplicitRk<T, S, C> {
+    fn control(&self) -> &Control {
+        &self.control
+    }
+
+    fn control_mut(&mut self) -> &mut Control {
+        &mut self.control
+    }
+}
+
+impl<T, S, C> Iterator for ExplicitRk<T, S, C>
+where
+    C: Controller,
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.init.as_ref()[0];
+
+        if t > self.max || self.control.error.is_some() {
+            return None;
+        }
+
+        let k1 = match self.fsal.take() {
+            Some(k1) => k1,
+            None => call_soe(&mut self.soe, &self.init, &mut self.control.stats),
+        };
+
+        let mut rejections = 0;
+
+        loop {
+            if self.tol.is_some() {
+                self.h = self.control.step_size(t, self.h)?;
+            }
+
+            let h = self.h.min(self.clip);
+
+            self.k.clear();
+            self.k.push(k1);
+            stages(
+                &mut self.soe,
+                &self.tableau,
+                self.init,
+                h,
+                &mut self.k,
+                &mut self.control.stats,
+            );
+
+            let mut next = self.init + combine(self.tableau.b, &self.k) * h;
+            next.as_mut()[0] = t + h;
+
+            let accepted = match (&self.tol, self.tableau.embedded) {
+                (Some(tol), Some(embedded)) => {
+                    let mut error = next - (self.init + combine(embedded, &self.k) * h);
+                    error.as_mut()[0] = 0.0;
+
+                    let r = tol.norm(&error, &self.init, &next);
+                    let order = self.tableau.order.min(self.tableau.embedded_order) + 1;
+
+                    let factor = self.controller.factor(r, order);
+                    self.h = next_step_size(self.h, h, h * factor, r <= 1.0);
+
+                    self.control.record(StepRecord {
+                        t,
+                        h,
+                        error: r,
+                        accepted: r <= 1.0,
+                    });
+
+                    r <= 1.0
+                }
+                _ => true,
+            };
+
+            if accepted {
+                if self.tableau.is_fsal() {
+                    self.fsal = self.k.last().copied();
+                }
+
+                self.control.stats.accepted += 1;
+                self.init = next;
+                self.clip = f64::INFINITY;
+
+                return Some(next);
+            }
+
+            rejections += 1;
+            self.control.reject(t, rejections)?;
+        }
+    }
+}
+
+impl<T, S, C> Solver for ExplicitRk<T, S, C>
+where
+    C: Controller,
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.control.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_explicit_rk() {
+    use super::tableau::{ALL, FEHLBERG, RK4};
+    use crate::twobody::{max_error, observed_order, test_system};
+
+    let system = test_system();
+
+    for tableau in &ALL {
+        let rk = |h| system.construct_explicit_rk(h, *tableau);
+
+        // Halving the step reduces the error by 2^order at least
+        let order = observed_order(&system, rk, 0.04, 2.0);
+        assert!(order > tableau.order as f64 - 0.5, "{:?}", tableau);
+        assert!(order < tableau.order as f64 + 1.5, "{:?}", tableau);
+    }
+
+    for tableau in ALL.iter().filter(|t| t.embedded.is_some()) {
+        let mut rk = system
+            .construct_explicit_rk(0.01, *tableau)
+            .adaptive(1e-9, 20.0);
+
+        for state in rk.by_ref() {
+            assert!(max_error(&system, state) < 1e-5);
+        }
+
+        // The first stage is evaluated once per step or once at all
+        let stats = rk.stats();
+        let first = if tableau.is_fsal() { 1 } else { stats.accepted };
+        assert_eq!(
+            stats.rhs_calls,
+            (stats.accepted + stats.rejected) * (tableau.stages() - 1) + first
+        );
+    }
+
+    // Rk4 is the engine with RK4 tableau
+    let rk4 = system.construct_rk4(0.01).nth(99).unwrap();
+    let engine = system.construct_explicit_rk(0.01, RK4).nth(99).unwrap();
+    assert_eq!(rk4.data, engine.data);
+
+    // Rk45 is the engine with FEHLBERG tableau in adaptive mode
+    let rk45 = system.construct_rk45(0.01, 1e-9, 20.0).nth(99).unwrap();
+    let engine = system
+        .construct_explicit_rk(0.01, FEHLBERG)
+        .adaptive(1e-9, 20.0)
+        .nth(99)
+        .unwrap();
+    assert_eq!(rk45.data, engine.data);
+}
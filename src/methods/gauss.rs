@@ -0,0 +1,295 @@
+use super::{call_soe, lagrange_weights, state_abs, Solver, Stats, StepError};
+use crate::soe::Soe;
+
+use std::f64::consts::PI;
+use std::iter::Iterator;
+use std::ops::{Add, Mul, Sub};
+
+/// Roots of the Legendre polynomial of degree `n` shifted to [0, 1]
+fn legendre_roots(n: usize) -> Vec<f64> {
+    (0..n)
+        .map(|i| {
+            // Newton's method from the asymptotic estimate of the root
+            let mut x = -(PI * (i as f64 + 0.75) / (n as f64 + 0.5)).cos();
+
+            for _ in 0..100 {
+                // P(x) and P'(x) from Bonnet's recursion
+                let (mut p0, mut p1) = (1.0, x);
+                for k in 2..=n {
+                    let k = k as f64;
+                    let p2 = ((2.0 * k - 1.0) * x * p1 - (k - 1.0) * p0) / k;
+                    p0 = p1;
+                    p1 = p2;
+                }
+                let dp = n as f64 * (x * p1 - p0) / (x * x - 1.0);
+
+                let dx = p1 / dp;
+                x -= dx;
+
+                if dx.abs() < 1e-16 {
+                    break;
+                }
+            }
+
+            (x + 1.0) / 2.0
+        })
+        .collect()
+}
+
+/// Butcher tableau `(a, b)` of Gauss–Legendre method with `stages`
+pub fn gauss_legendre(stages: usize) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let c = legendre_roots(stages);
+
+    let a = c.iter().map(|&ci| lagrange_weights(&c, ci)).collect();
+    let b = lagrange_weights(&c, 1.0);
+
+    (a, b)
+}
+
+/// Gauss–Legendre implicit Runge–Kutta method of order `2 * stages`
+///
+/// The method is symplectic and A-stable, so energy error
+/// is bounded on long runs. Stages are found by fixed-point
+/// iteration which converges when `h` is less than the inverse
+/// Lipschitz constant of `f`
+pub struct GaussLegendre<T, S> {
+    init: T,
+    soe: S,
+    h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    a: Vec<Vec<f64>>,
+    b: Vec<f64>,
+    // Derivatives in stages of the last step
+    k: Vec<T>,
+    // Relative tolerance and max number of iterations
+    tol: f64,
+    max_iterations: usize,
+    error: Option<StepError>,
+    stats: Stats,
+}
+
+impl<T, S> GaussLegendre<T, S> {
+    pub fn new(init: T, soe: S, h: f64, stages: usize) -> Self {
+        assert!(stages > 0, "method must have stages");
+
+        let (a, b) = gauss_legendre(stages);
+
+        Self {
+            init,
+            soe,
+            h,
+            clip: f64::INFINITY,
+            a,
+            b,
+            k: Vec::with_capacity(stages),
+            tol: 1e-14,
+            max_iterations: 100,
+            error: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Set relative tolerance `tol` and max number of iterations
+    /// of the fixed-point solve, 1e-14 and 100 by default
+    pub fn iterations(mut self, tol: f64, max_iterations: usize) -> Self {
+        self.tol = tol;
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Error which stopped the method
+    pub fn error(&self) -> Option<StepError> {
+        self.error
+    }
+}
+
+impl<T, S> Iterator for GaussLegendre<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let init = self.init;
+        let h = self.h.min(self.clip);
+        // The time is left out, so convergence does not depend on it
+        let scale = 1.0 + state_abs(&init);
+
+        // Start from the derivative in the init point
+        let f = call_soe(&mut self.soe, &init, &mut self.stats);
+        self.k.clear();
+        self.k.resize(self.b.len(), f);
+
+        for _ in 0..self.max_iterations {
+            let mut change = 0.0f64;
+
+            for i in 0..self.k.len() {
+                let mut args = T::default();
+                for (&a, &k) in self.a[i].iter().zip(&self.k) {
+                    args = args + k * a;
+                }
+
+                let k = call_soe(&mut self.soe, &(init + args * h), &mut self.stats);
+
+                change = change.max(state_abs(&(k - self.k[i])) * h);
+                self.k[i] = k;
+            }
+
+            if change <= self.tol * scale {
+                let mut next = T::default();
+                for (&b, &k) in self.b.iter().zip(&self.k) {
+                    next = next + k * b;
+                }
+
+                let mut next = init + next * h;
+                next.as_mut()[0] = init.as_ref()[0] + h;
+
+                self.init = next;
+                self.clip = f64::INFINITY;
+                self.stats.accepted += 1;
+
+                return Some(next);
+            }
+        }
+
+        self.error = Some(StepError::NoConvergence {
+            t: init.as_ref()[0],
+            iterations: self.max_iterations,
+        });
+
+        None
+    }
+}
+
+impl<T, S> Solver for GaussLegendre<T, S>
+where
+    S: Soe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_gauss_legendre_tableau() {
+    let sqrt3 = 3.0f64.sqrt();
+    let (a, b) = gauss_legendre(2);
+
+    let expected = [[0.25, 0.25 - sqrt3 / 6.0], [0.25 + sqrt3 / 6.0, 0.25]];
+    for i in 0..2 {
+        for j in 0..2 {
+            assert!((a[i][j] - expected[i][j]).abs() < 1e-14);
+        }
+    }
+    assert!((b[0] - 0.5).abs() < 1e-14 && (b[1] - 0.5).abs() < 1e-14);
+
+    let sqrt15 = 15.0f64.sqrt();
+    let c = legendre_roots(3);
+    assert!((c[0] - (0.5 - sqrt15 / 10.0)).abs() < 1e-14);
+    assert!((c[1] - 0.5).abs() < 1e-14);
+    assert!((c[2] - (0.5 + sqrt15 / 10.0)).abs() < 1e-14);
+
+    let (_, b) = gauss_legendre(3);
+    let expected = [5.0 / 18.0, 4.0 / 9.0, 5.0 / 18.0];
+    for (b, e) in b.iter().zip(&expected) {
+        assert!((b - e).abs() < 1e-14);
+    }
+
+    // Quadrature of order 2s is exact for x^(2s - 1)
+    for stages in 1..=6 {
+        let c = legendre_roots(stages);
+        let (_, b) = gauss_legendre(stages);
+        let p = 2 * stages as i32 - 1;
+
+        let integral: f64 = b.iter().zip(&c).map(|(b, c)| b * c.powi(p)).sum();
+        assert!((integral - 1.0 / (p + 1) as f64).abs() < 1e-13);
+    }
+}
+
+#[test]
+fn test_gauss_legendre() {
+    use crate::twobody::{observed_order, test_system};
+
+    let system = test_system();
+
+    for stages in 2..=4 {
+        let gauss = |h| system.construct_gauss_legendre(h, stages);
+        let order = observed_order(&system, gauss, 0.2, 2.0);
+        assert!(order > 2.0 * stages as f64 - 0.5);
+    }
+
+    // Energy error is bounded on a long run
+    let e0 = system.energy(system.get_init());
+    let steps = 5000;
+
+    for &(stages, bound) in &[(2, 1e-4), (3, 1e-6), (4, 1e-9)] {
+        let mut gauss = system.construct_gauss_legendre(0.1, stages);
+        let drift: Vec<_> = gauss
+            .by_ref()
+            .take(steps)
+            .map(|state| (system.energy(state) - e0).abs())
+            .collect();
+
+        assert!(gauss.error().is_none());
+
+        let (first, last) = drift.split_at(steps / 10);
+        let first = first.iter().cloned().fold(0.0, f64::max);
+        let last = last.iter().cloned().fold(0.0, f64::max);
+
+        assert!(last < bound);
+        assert!(last < first * 1.1);
+    }
+
+    // Fixed-point iteration diverges with a huge step
+    let mut gauss = system
+        .construct_gauss_legendre(50.0, 2)
+        .iterations(1e-14, 5);
+    assert!(gauss.next().is_none());
+    assert!(matches!(
+        gauss.error(),
+        Some(StepError::NoConvergence { iterations: 5, .. })
+    ));
+
+    // Convergence does not depend on the init time
+    let solve = |t0: f64| {
+        let mut init = system.get_init();
+        init[0] = t0;
+
+        let mut gauss = GaussLegendre::new(init, system.generate_soe(), 0.1, 4);
+        let last = gauss.nth(999).unwrap();
+        (last, gauss.stats().rhs_calls)
+    };
+
+    let (last, calls) = solve(0.0);
+    let (far, far_calls) = solve(1e8);
+
+    assert_eq!(calls, far_calls);
+    assert!((1..5).all(|i| (last[i] - far[i]).abs() < 1e-12));
+}
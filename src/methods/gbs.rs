@@ -0,0 +1,339 @@
+use super::{
+    call_soe,
+    controller::{Controller, Integral},
+    next_step_size, Adaptive, Control, Solver, Stats, StepRecord, Tolerance,
+};
+use crate::soe::Soe;
+
+use std::iter::Iterator;
+use std::ops::{Add, Mul, Sub};
+
+/// Max number of rows of the extrapolation table of `Gbs`
+pub const MAX_COLUMNS: usize = 12;
+
+/// Number of substeps of the midpoint rule in row `j`
+/// of the extrapolation table: 2, 4, 6, ...
+fn substeps(j: usize) -> usize {
+    2 * (j + 1)
+}
+
+/// Gragg–Bulirsch–Stoer extrapolation method
+///
+/// Step is made by the modified midpoint rule with `2, 4, 6, ...`
+/// substeps, the results are extrapolated to the zero substep with
+/// polynomials in `h^2`. Row `j` of the table gives the solution
+/// of order `2 * (j + 1)`, its error is estimated with the previous
+/// column. Both step size and number of rows are chosen to minimize
+/// calls of `soe` per unit of time.
+///
+/// Step size factors of the rows are found with copies of the
+/// controller, only the chosen row updates its state
+pub struct Gbs<T, S, C = Integral> {
+    init: T,
+    soe: S,
+    h: f64,
+    tol: Tolerance,
+    max: f64,
+    // Target row of the next step and the max number of rows
+    k: usize,
+    columns: usize,
+    // The last row of the extrapolation table
+    table: Vec<T>,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    control: Control,
+    controller: C,
+}
+
+impl<T, S> Gbs<T, S> {
+    /// Create method with init `h` step, `tol` tolerance and `max` time
+    pub fn new(init: T, soe: S, h: f64, tol: impl Into<Tolerance>, max: f64) -> Self {
+        Self {
+            init,
+            soe,
+            h,
+            tol: tol.into(),
+            max,
+            k: 4,
+            columns: 8,
+            table: Vec::with_capacity(MAX_COLUMNS),
+            clip: f64::INFINITY,
+            control: Control::default(),
+            controller: Integral::default(),
+        }
+    }
+}
+
+impl<T, S, C> Gbs<T, S, C> {
+    /// Set step size controller, `Integral` by default
+    pub fn controller<C2: Controller + Clone>(self, controller: C2) -> Gbs<T, S, C2> {
+        Gbs {
+            init: self.init,
+            soe: self.soe,
+            h: self.h,
+            tol: self.tol,
+            max: self.max,
+            k: self.k,
+            columns: self.columns,
+            table: self.table,
+            clip: self.clip,
+            control: self.control,
+            controller,
+        }
+    }
+
+    /// Set max number of rows of the extrapolation table, 8 by default,
+    /// so the max order of the method is `2 * columns`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `columns` is not from 2 to `MAX_COLUMNS`
+    pub fn columns(mut self, columns: usize) -> Self {
+        assert!(
+            (2..=MAX_COLUMNS).contains(&columns),
+            "columns must be from 2 to {}",
+            MAX_COLUMNS
+        );
+
+        self.columns = columns;
+        self.k = self.k.min(columns - 1);
+        self
+    }
+
+    /// Step size of the next attempt
+    pub fn h(&self) -> f64 {
+        self.h
+    }
+
+    /// Order of the solution of the next step
+    pub fn order(&self) -> usize {
+        2 * (self.k + 1)
+    }
+}
+
+impl<T, S, C> Adaptive for Gbs<T, S, C> {
+    fn control(&self) -> &Control {
+        &self.control
+    }
+
+    fn control_mut(&mut self) -> &mut Control {
+        &mut self.control
+    }
+}
+
+impl<T, S, C> Gbs<T, S, C>
+where
+    S: Soe<Args = T>,
+    T: Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T> + Sub<T, Output = T>,
+{
+    /// Modified midpoint rule over `h` with `n` substeps,
+    /// `f` is the derivative in `init`
+    fn midpoint(&mut self, f: T, n: usize, h: f64) -> T {
+        let h = h / n as f64;
+
+        let mut prev = self.init;
+        let mut current = self.init + f * h;
+
+        for _ in 1..n {
+            let next =
+                prev + call_soe(&mut self.soe, &current, &mut self.control.stats) * (2.0 * h);
+            prev = current;
+            current = next;
+        }
+
+        current
+    }
+}
+
+impl<T, S, C> Iterator for Gbs<T, S, C>
+where
+    C: Controller + Clone,
+    S: Soe<Args = T>,
+    T: Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.init.as_ref()[0];
+
+        if t > self.max || self.control.error.is_some() {
+            return None;
+        }
+
+        let f = call_soe(&mut self.soe, &self.init, &mut self.control.stats);
+
+        let mut rejections = 0;
+
+        loop {
+            self.h = self.control.step_size(t, self.h)?;
+
+            let h = self.h.min(self.clip);
+
+            // Calls of `soe`, error norms and step size factors of the rows
+            let mut costs = vec![1.0];
+            let mut errors = vec![0.0];
+            let mut factors = vec![0.0];
+            let mut r = 0.0;
+            let mut accepted = false;
+
+            self.table.clear();
+
+            for j in 0..=(self.k + 1).min(self.columns - 1) {
+                let n = substeps(j);
+
+                let mut row = vec![self.midpoint(f, n, h)];
+                row[0].as_mut()[0] = t + h;
+                costs.push(costs[j] + (n - 1) as f64);
+
+                // Aitken–Neville extrapolation to the zero substep
+                for l in 1..=j {
+                    let ratio = (n as f64 / substeps(j - l) as f64).powi(2);
+                    let diff = (row[l - 1] - self.table[l - 1]) * (1.0 / (ratio - 1.0));
+                    row.push(row[l - 1] + diff);
+                }
+
+                self.table = row;
+
+                if j == 0 {
+                    continue;
+                }
+
+                let mut error = self.table[j] - self.table[j - 1];
+                error.as_mut()[0] = 0.0;
+
+                r = self.tol.norm(&error, &self.init, &self.table[j]);
+                errors.push(r);
+                factors.push(self.controller.clone().factor(r, 2 * j as i32 + 1));
+
+                if j + 1 >= self.k && r <= 1.0 {
+                    accepted = true;
+                    break;
+                }
+            }
+
+            self.control.record(StepRecord {
+                t,
+                h,
+                error: r,
+                accepted,
+            });
+
+            // Row with the min work per unit of time, the order
+            // decreases at most by one row per step
+            let last = factors.len() - 1;
+            let first = if accepted { last } else { self.k }.max(2) - 1;
+            let work = |j: usize| costs[j + 1] / factors[j];
+            let best = (first..=last)
+                .min_by(|&i, &j| work(i).partial_cmp(&work(j)).unwrap())
+                .unwrap_or(1);
+
+            if accepted && best == last && last + 1 < self.columns {
+                // Try one more row with the same work per unit of time
+                let next_cost = costs[last + 1] + (substeps(last + 1) - 1) as f64;
+                let factor = self.controller.factor(errors[last], 2 * last as i32 + 1);
+
+                self.k = last + 1;
+                self.h = next_step_size(
+                    self.h,
+                    h,
+                    h * factor * next_cost / costs[last + 1],
+                    accepted,
+                );
+            } else {
+                let factor = self.controller.factor(errors[best], 2 * best as i32 + 1);
+
+                self.k = best;
+                self.h = next_step_size(self.h, h, h * factor, accepted);
+            }
+
+            if accepted {
+                let next = self.table[last];
+
+                self.control.stats.accepted += 1;
+                self.init = next;
+                self.clip = f64::INFINITY;
+
+                return Some(next);
+            }
+
+            rejections += 1;
+            self.control.reject(t, rejections)?;
+        }
+    }
+}
+
+impl<T, S, C> Solver for Gbs<T, S, C>
+where
+    C: Controller + Clone,
+    S: Soe<Args = T>,
+    T: Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.control.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_gbs() {
+    use super::controller::Pi;
+    use crate::twobody::{max_error, test_system};
+
+    let system = test_system();
+
+    let gbs = |tol: f64| {
+        let mut gbs = system.construct_gbs(0.1, tol, 50.0);
+        let last = gbs.by_ref().last().unwrap();
+        assert!(gbs.error().is_none());
+
+        (max_error(&system, last), gbs)
+    };
+
+    let (loose, _) = gbs(1e-9);
+    let (tight, tight_gbs) = gbs(1e-12);
+
+    assert!(tight < 1e-7);
+    assert!(tight < loose / 100.0);
+
+    // High orders are cheaper than dopri5 with tight tolerance
+    let mut dopri5 = system.construct_dopri5(0.1, 1e-12, 50.0);
+    dopri5.by_ref().count();
+
+    assert!(tight_gbs.order() > 10);
+    assert!(tight_gbs.stats().rhs_calls < dopri5.stats().rhs_calls / 2);
+
+    // Order is limited by the number of columns
+    let mut gbs = system.construct_gbs(0.1, 1e-9, 50.0).columns(3);
+    let mut max_order = 0;
+    while gbs.next().is_some() {
+        max_order = max_order.max(gbs.order());
+    }
+
+    assert!(max_order <= 6);
+    assert!(gbs.error().is_none());
+
+    // Any step size controller
+    let mut gbs = system
+        .construct_gbs(0.1, 1e-12, 50.0)
+        .controller(Pi::default());
+    let last = gbs.by_ref().last().unwrap();
+    assert!(gbs.error().is_none());
+    assert!(max_error(&system, last) < 1e-7);
+}
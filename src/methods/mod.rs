@@ -1,21 +1,197 @@
 use crate::soe::Soe;
 
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul};
+
 pub mod ab2;
+pub mod abm;
 pub mod am2;
+pub mod controller;
+pub mod dopri5;
 pub mod euler;
+pub mod events;
+pub mod explicit_rk;
+pub mod gauss;
+pub mod gbs;
+pub mod output;
 pub mod rk4;
 pub mod rk45;
+pub mod rkn;
+pub mod symplectic;
+pub mod tableau;
+pub mod terminate;
+
+/// Solver of the system of equations
+pub trait Solver: Iterator {
+    /// Statistics collected since the solver was created
+    fn stats(&self) -> Stats;
+
+    /// The last value, the init value before the first step
+    fn current(&self) -> Self::Item;
+
+    /// Make the next step not longer than `h`
+    ///
+    /// Fixed step methods return to their step after it,
+    /// multistep methods restart with their `Starter`
+    fn clip(&mut self, h: f64);
+
+    /// Solution at time `t` between the last two values,
+    /// `None` if the method has no dense output
+    fn dense(&self, _t: f64) -> Option<Self::Item> {
+        None
+    }
+
+    /// The method has dense output, it is available
+    /// after the first step
+    fn has_dense(&self) -> bool {
+        false
+    }
+}
+
+impl<I: Solver + ?Sized> Solver for &mut I {
+    fn stats(&self) -> Stats {
+        (**self).stats()
+    }
+
+    fn current(&self) -> Self::Item {
+        (**self).current()
+    }
+
+    fn clip(&mut self, h: f64) {
+        (**self).clip(h);
+    }
+
+    fn dense(&self, t: f64) -> Option<Self::Item> {
+        (**self).dense(t)
+    }
+
+    fn has_dense(&self) -> bool {
+        (**self).has_dense()
+    }
+}
+
+/// Statistics of a solver
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    /// Number of evaluations of the right-hand side
+    pub rhs_calls: usize,
+    /// Number of accepted steps
+    pub accepted: usize,
+    /// Number of rejected steps (adaptive methods only)
+    pub rejected: usize,
+}
+
+/// One-step method which makes the first steps of multistep methods
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Starter {
+    /// Heun's method, see `Heun`
+    Heun,
+    /// Classical Runge–Kutta 4th order method
+    #[default]
+    Rk4,
+}
+
+impl Starter {
+    /// Make one step with `h` from `init` with derivative `f` in it
+    fn step<T, S>(self, soe: &mut S, init: T, f: T, h: f64, stats: &mut Stats) -> T
+    where
+        S: Soe<Args = T>,
+        T: Default
+            + Copy
+            + AsMut<[f64]>
+            + Add<T, Output = T>
+            + Mul<f64, Output = T>
+            + Div<f64, Output = T>,
+    {
+        match self {
+            Starter::Heun => euler::step(soe, init, f, h, stats),
+            Starter::Rk4 => explicit_rk::step(soe, &tableau::RK4, init, f, h, stats),
+        }
+    }
+}
 
-fn call_soe<T, S>(soe: &mut S, args: &T) -> T
+/// Step attempt of an adaptive method
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepRecord {
+    /// Time at the start of the step
+    pub t: f64,
+    /// Step size
+    pub h: f64,
+    /// Weighted norm of the local error estimate,
+    /// see `Tolerance`
+    pub error: f64,
+    /// Step is accepted
+    pub accepted: bool,
+}
+
+fn call_soe<T, S>(soe: &mut S, args: &T, stats: &mut Stats) -> T
 where
     T: AsMut<[f64]>,
     S: Soe<Args = T>,
 {
-    let mut result = soe.call(&args);
+    stats.rhs_calls += 1;
+
+    let mut result = soe.call(args);
     result.as_mut()[0] = 1.0f64;
     result
 }
 
+/// Cubic Hermite interpolation between `y0` and `y1` with derivatives
+/// `f0` and `f1` at `theta` part of the step `h`
+///
+/// The interpolation is 3rd order accurate, time component
+/// is interpolated exactly
+fn hermite<T>(y0: T, f0: T, y1: T, f1: T, h: f64, theta: f64) -> T
+where
+    T: Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    let theta2 = theta * theta;
+    let theta3 = theta2 * theta;
+
+    y0 * (2.0 * theta3 - 3.0 * theta2 + 1.0)
+        + f0 * (h * (theta3 - 2.0 * theta2 + theta))
+        + y1 * (3.0 * theta2 - 2.0 * theta3)
+        + f1 * (h * (theta3 - theta2))
+}
+
+/// Integrals over [0, `x`] of Lagrange basis polynomials on `nodes`
+///
+/// Value with these weights is the integral of the polynomial
+/// interpolating values in `nodes`
+fn lagrange_weights(nodes: &[f64], x: f64) -> Vec<f64> {
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(j, &sj)| {
+            // poly[p] is the coefficient of s^p
+            let mut poly = vec![1.0];
+
+            for (i, &si) in nodes.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+
+                let d = sj - si;
+                let mut next = vec![0.0; poly.len() + 1];
+
+                for (p, &c) in poly.iter().enumerate() {
+                    next[p + 1] += c / d;
+                    next[p] -= c * si / d;
+                }
+
+                poly = next;
+            }
+
+            poly.iter()
+                .enumerate()
+                .map(|(p, c)| c * x.powi(p as i32 + 1) / (p + 1) as f64)
+                .sum()
+        })
+        .collect()
+}
+
+/// Euclidean norm of the components of `v`
 pub fn abs<T>(v: &T) -> f64
 where
     T: AsRef<[f64]>,
@@ -28,3 +204,419 @@ where
 
     result.sqrt()
 }
+
+/// Euclidean norm of the state `v` without the time component
+pub(crate) fn state_abs<T>(v: &T) -> f64
+where
+    T: AsRef<[f64]>,
+{
+    let mut result = 0.0;
+
+    for el in &v.as_ref()[1..] {
+        result += el * el;
+    }
+
+    result.sqrt()
+}
+
+/// Absolute and relative tolerances of adaptive methods
+///
+/// Error of a component `i` is scaled by
+/// `atol[i] + rtol[i] * max(|y0[i]|, |y1[i]|)` where `y0` and `y1`
+/// are the states at the step ends, the step is accepted when
+/// the RMS of the scaled errors is not greater than 1
+///
+/// Tolerances are either scalar or one value per state component,
+/// the time component is ignored
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tolerance {
+    atol: Vec<f64>,
+    rtol: Vec<f64>,
+}
+
+impl Tolerance {
+    /// Same tolerances for every component
+    pub fn new(atol: f64, rtol: f64) -> Self {
+        Self {
+            atol: vec![atol],
+            rtol: vec![rtol],
+        }
+    }
+
+    /// Tolerances per component of the state without the time:
+    /// `2 * N` values for the positions and then the velocities
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ or are not even
+    pub fn per_component(atol: &[f64], rtol: &[f64]) -> Self {
+        assert!(atol.len() == rtol.len(), "atol and rtol lengths differ");
+        assert!(
+            !atol.is_empty() && atol.len().is_multiple_of(2),
+            "tolerances should cover positions and velocities"
+        );
+
+        Self {
+            atol: atol.to_vec(),
+            rtol: rtol.to_vec(),
+        }
+    }
+
+    /// Weighted RMS norm of the error estimate `error`
+    /// of the step from `y0` to `y1`
+    pub fn norm<T>(&self, error: &T, y0: &T, y1: &T) -> f64
+    where
+        T: AsRef<[f64]>,
+    {
+        let (error, y0, y1) = (error.as_ref(), y0.as_ref(), y1.as_ref());
+
+        assert!(
+            self.atol.len() == 1 || self.atol.len() + 1 == error.len(),
+            "tolerances don't match the state length"
+        );
+
+        // Component `i` of the state, the time is not included
+        let component = |values: &[f64], i: usize| values[(i - 1).min(values.len() - 1)];
+
+        let mut result = 0.0;
+
+        for i in 1..error.len() {
+            let scale =
+                component(&self.atol, i) + component(&self.rtol, i) * y0[i].abs().max(y1[i].abs());
+
+            result += (error[i] / scale).powi(2);
+        }
+
+        (result / (error.len() - 1) as f64).sqrt()
+    }
+}
+
+/// Both tolerances are `e`
+impl From<f64> for Tolerance {
+    fn from(e: f64) -> Self {
+        Self::new(e, e)
+    }
+}
+
+/// Limits of the step size control of adaptive methods
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepLimits {
+    /// Minimal step size
+    pub h_min: f64,
+    /// Maximal step size
+    pub h_max: f64,
+    /// Maximal number of rejected steps in a row
+    pub max_rejections: usize,
+}
+
+impl Default for StepLimits {
+    fn default() -> Self {
+        Self {
+            h_min: 0.0,
+            h_max: f64::INFINITY,
+            max_rejections: 50,
+        }
+    }
+}
+
+/// State of the step size control shared by adaptive methods
+#[derive(Debug, Clone, Default)]
+pub struct Control {
+    limits: StepLimits,
+    error: Option<StepError>,
+    records: Option<Vec<StepRecord>>,
+    stats: Stats,
+}
+
+/// Size of the step after the step with `h` clipped from `proposed`
+/// (see `Solver::clip`), `h_next` is proposed by the controller.
+/// Accepted clipped step does not shrink the next one
+fn next_step_size(proposed: f64, h: f64, h_next: f64, accepted: bool) -> f64 {
+    if accepted && h < proposed {
+        proposed.max(h_next)
+    } else {
+        h_next
+    }
+}
+
+impl Control {
+    /// Step size `h` at time `t` limited by the max step,
+    /// `None` with `StepTooSmall` error if it is too small
+    fn step_size(&mut self, t: f64, h: f64) -> Option<f64> {
+        let h = h.min(self.limits.h_max);
+
+        if h < self.limits.h_min || t + h == t {
+            self.error = Some(StepError::StepTooSmall { t, h });
+            return None;
+        }
+
+        Some(h)
+    }
+
+    /// Keep `record` of the step attempt if the records are on
+    fn record(&mut self, record: StepRecord) {
+        if let Some(records) = &mut self.records {
+            records.push(record);
+        }
+    }
+
+    /// Count the rejected step at time `t` after `rejections` in a row,
+    /// `None` with `TooManyRejections` error if there are too many
+    fn reject(&mut self, t: f64, rejections: usize) -> Option<()> {
+        self.stats.rejected += 1;
+
+        if rejections > self.limits.max_rejections {
+            self.error = Some(StepError::TooManyRejections { t, rejections });
+            return None;
+        }
+
+        Some(())
+    }
+}
+
+/// Adaptive method with step size `Control`
+///
+/// Builders and accessors of the step size control are shared
+/// by the methods through this trait
+pub trait Adaptive: Sized {
+    /// State of the step size control
+    fn control(&self) -> &Control;
+
+    /// Mutable state of the step size control
+    fn control_mut(&mut self) -> &mut Control;
+
+    /// Set limits of the step size control
+    fn limits(mut self, limits: StepLimits) -> Self {
+        self.control_mut().limits = limits;
+        self
+    }
+
+    /// Error which stopped the method
+    fn error(&self) -> Option<StepError> {
+        self.control().error
+    }
+
+    /// Keep record of every step attempt
+    fn record_steps(mut self) -> Self {
+        self.control_mut().records = Some(Vec::new());
+        self
+    }
+
+    /// Step attempts recorded since `record_steps` was set
+    fn records(&self) -> &[StepRecord] {
+        self.control().records.as_deref().unwrap_or(&[])
+    }
+}
+
+/// Errors of methods, the method stops after them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepError {
+    /// Required step `h` at time `t` is less than the minimal step
+    /// or too small to change `t`
+    StepTooSmall { t: f64, h: f64 },
+    /// Step at time `t` is rejected `rejections` times in a row
+    TooManyRejections { t: f64, rejections: usize },
+    /// Iteration of implicit method at time `t` doesn't converge
+    /// after `iterations`
+    NoConvergence { t: f64, iterations: usize },
+}
+
+impl Display for StepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepError::StepTooSmall { t, h } => write!(f, "step {} is too small at t = {}", h, t),
+            StepError::TooManyRejections { t, rejections } => {
+                write!(f, "step is rejected {} times at t = {}", rejections, t)
+            }
+            StepError::NoConvergence { t, iterations } => {
+                write!(
+                    f,
+                    "no convergence after {} iterations at t = {}",
+                    iterations, t
+                )
+            }
+        }
+    }
+}
+
+impl Error for StepError {}
+
+#[test]
+fn test_tolerance_norm() {
+    use crate::vector::Vector;
+
+    let y0: Vector<f64, 3> = [0.0, 1.0, -100.0].into();
+    let y1: Vector<f64, 3> = [1.0, 2.0, 100.0].into();
+    // Error in time component is ignored
+    let error: Vector<f64, 3> = [1e10, 0.3, 30.0].into();
+
+    let tol = Tolerance::new(0.1, 0.1);
+    let expected = ((0.3f64 / 0.3).powi(2) + (30.0f64 / 10.1).powi(2)) / 2.0;
+    assert!((tol.norm(&error, &y0, &y1) - expected.sqrt()).abs() < 1e-12);
+
+    let tol = Tolerance::per_component(&[0.1, 0.0], &[0.0, 0.3]);
+    assert!((tol.norm(&error, &y0, &y1) - 5.0f64.sqrt()).abs() < 1e-12);
+
+    assert_eq!(Tolerance::from(1e-6), Tolerance::new(1e-6, 1e-6));
+}
+
+#[test]
+#[should_panic(expected = "tolerances should cover positions and velocities")]
+fn test_tolerance_length() {
+    // The time has no tolerance
+    Tolerance::per_component(&[0.0, 0.1, 0.0], &[0.0, 0.0, 0.3]);
+}
+
+#[test]
+fn test_step_errors() {
+    use crate::twobody::{test_system, Body2d, TwoBodySystem2d};
+
+    let mut solver = test_system().construct_rk45(0.01, 1e-9, 20.0);
+    assert!(solver.by_ref().last().unwrap()[0] > 20.0);
+    assert_eq!(solver.error(), None);
+
+    // Radial fall ends with collision, where the step can't be made
+    let collision = TwoBodySystem2d::new(
+        Body2d {
+            m: 5.0,
+            pos: [0.0, 0.0].into(),
+            velocity: [0.0, 0.0].into(),
+        },
+        Body2d {
+            m: 5.0,
+            pos: [1.0, 0.0].into(),
+            velocity: [0.0, 0.0].into(),
+        },
+        0.1,
+    );
+
+    let mut rk45 = collision.construct_rk45(0.01, 1e-9, 20.0);
+    let t = rk45.by_ref().last().unwrap()[0];
+    assert!(t < 20.0);
+    assert!(rk45.error().is_some());
+    assert!(rk45.next().is_none());
+
+    let mut dopri5 = collision.construct_dopri5(0.01, 1e-9, 20.0);
+    assert!(dopri5.by_ref().last().unwrap()[0] < 20.0);
+    assert!(dopri5.error().is_some());
+
+    let limits = StepLimits {
+        h_min: 1e-3,
+        ..StepLimits::default()
+    };
+    let mut rk45 = collision.construct_rk45(0.01, 1e-9, 20.0).limits(limits);
+    rk45.by_ref().last();
+    assert!(matches!(
+        rk45.error(),
+        Some(StepError::StepTooSmall { h, .. }) if h < 1e-3
+    ));
+
+    let limits = StepLimits {
+        max_rejections: 0,
+        ..StepLimits::default()
+    };
+    let mut rk45 = collision.construct_rk45(1.0, 1e-9, 20.0).limits(limits);
+    assert!(rk45.next().is_none());
+    assert!(matches!(
+        rk45.error(),
+        Some(StepError::TooManyRejections { rejections: 1, .. })
+    ));
+}
+
+#[test]
+fn test_stats() {
+    use crate::twobody::test_system;
+
+    let system = test_system();
+
+    let mut rk4 = system.construct_rk4(0.01);
+    rk4.by_ref().take(100).count();
+    assert_eq!(
+        rk4.stats(),
+        Stats {
+            rhs_calls: 400,
+            accepted: 100,
+            rejected: 0
+        }
+    );
+
+    let mut yoshida4 = system.construct_yoshida4(0.01);
+    yoshida4.by_ref().take(100).count();
+    assert_eq!(yoshida4.stats().rhs_calls, 301);
+
+    // Too large init step must be rejected
+    let mut rk45 = system.construct_rk45(1.0, 1e-9, 5.0).record_steps();
+    rk45.by_ref().count();
+    let stats = rk45.stats();
+    assert!(stats.rejected > 0);
+    // The first stage is reused after rejection
+    assert_eq!(stats.rhs_calls, 6 * stats.accepted + 5 * stats.rejected);
+    assert_eq!(rk45.records().len(), stats.accepted + stats.rejected);
+    assert!(!rk45.records()[0].accepted);
+
+    let mut dopri5 = system.construct_dopri5(1.0, 1e-9, 5.0).record_steps();
+    dopri5.by_ref().count();
+    let stats = dopri5.stats();
+    assert_eq!(stats.rhs_calls, 1 + 6 * (stats.accepted + stats.rejected));
+
+    let records = dopri5.records();
+    assert_eq!(records.len(), stats.accepted + stats.rejected);
+    assert_eq!(
+        records.iter().filter(|r| r.accepted).count(),
+        stats.accepted
+    );
+    for record in records {
+        assert_eq!(record.accepted, record.error <= 1.0);
+    }
+    for pair in records.windows(2) {
+        // Next attempt starts where the accepted step ends
+        if pair[0].accepted {
+            assert_eq!(pair[1].t, pair[0].t + pair[0].h);
+        } else {
+            assert_eq!(pair[1].t, pair[0].t);
+        }
+    }
+}
+
+#[test]
+fn test_clip() {
+    use crate::twobody::test_system;
+
+    let system = test_system();
+
+    // Clipped step lands on the time and does not shrink the next one
+    let mut rk45 = system.construct_rk45(0.01, 1e-9, f64::INFINITY);
+    let t = rk45.nth(9).unwrap()[0];
+    let h = rk45.h();
+    rk45.clip(h / 100.0);
+    assert_eq!(rk45.next().unwrap()[0], t + h / 100.0);
+    assert!(rk45.h() >= h);
+
+    let mut dopri5 = system.construct_dopri5(0.01, 1e-9, f64::INFINITY);
+    let t = dopri5.nth(9).unwrap()[0];
+    let h = dopri5.h();
+    dopri5.clip(h / 100.0);
+    assert_eq!(dopri5.next().unwrap()[0], t + h / 100.0);
+    assert!(dopri5.h() >= h);
+
+    let mut rkn = system.construct_rkn64(0.01, 1e-9, f64::INFINITY);
+    let t = rkn.nth(9).unwrap()[0];
+    let h = rkn.h();
+    rkn.clip(h / 100.0);
+    assert_eq!(rkn.next().unwrap()[0], t + h / 100.0);
+    assert!(rkn.h() >= h);
+
+    let mut gbs = system.construct_gbs(0.01, 1e-9, f64::INFINITY);
+    let t = gbs.nth(9).unwrap()[0];
+    let h = gbs.h();
+    gbs.clip(h / 100.0);
+    assert_eq!(gbs.next().unwrap()[0], t + h / 100.0);
+    assert!(gbs.h() >= h);
+
+    let mut abm = system.construct_abm(0.01, 8).variable(1e-9, f64::INFINITY);
+    let t = abm.nth(9).unwrap()[0];
+    let h = abm.h();
+    abm.clip(h / 100.0);
+    assert_eq!(abm.next().unwrap()[0], t + h / 100.0);
+    assert!(abm.h() >= h);
+}
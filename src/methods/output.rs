@@ -0,0 +1,251 @@
+use super::{Solver, Stats};
+
+use std::iter::Iterator;
+
+/// Regular grid of `count` times from `start` with `step`
+///
+/// Times are computed as `start + i * step`, so rounding
+/// errors are not accumulated
+pub fn grid(start: f64, step: f64, count: usize) -> impl Iterator<Item = f64> + Clone {
+    (0..count).map(move |i| start + step * i as f64)
+}
+
+/// Iterator adapter which returns values of the wrapped solver
+/// exactly at the given times
+///
+/// Value between two steps of the solver is taken from its dense
+/// output (see `Solver::has_dense`). Steps of methods without it
+/// are clipped with `Solver::clip` to land exactly on the times,
+/// multistep methods restart after such steps.
+///
+/// Times should be non-decreasing and not before the current time
+/// of the solver, the iterator ends at the first time out of order.
+/// It also ends when the times or the values of the solver end.
+///
+/// Should be created with `TwoBodySystem::output`
+pub struct Output<I, J, T> {
+    solver: I,
+    times: J,
+    // The last output value
+    last: T,
+    // Time which is not reached because of the clipped step
+    pending: Option<f64>,
+    // Max length of the next step
+    clip: f64,
+    finished: bool,
+}
+
+impl<I, J, T> Output<I, J, T> {
+    /// Output values of `solver` at `times` from its current value
+    pub fn new(solver: I, times: impl IntoIterator<IntoIter = J>) -> Self
+    where
+        I: Solver<Item = T>,
+    {
+        Self {
+            last: solver.current(),
+            solver,
+            times: times.into_iter(),
+            pending: None,
+            clip: f64::INFINITY,
+            finished: false,
+        }
+    }
+}
+
+impl<I, J, T> Iterator for Output<I, J, T>
+where
+    I: Solver<Item = T>,
+    J: Iterator<Item = f64>,
+    T: Copy + AsRef<[f64]> + AsMut<[f64]>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let t = self.pending.take().or_else(|| self.times.next())?;
+        let t0 = self.last.as_ref()[0];
+
+        if t < t0 || t.is_nan() {
+            self.finished = true;
+            return None;
+        }
+
+        // The clipped step ends before the next time
+        let t = if t - t0 > self.clip {
+            self.pending = Some(t);
+            t0 + self.clip
+        } else {
+            t
+        };
+        self.clip = f64::INFINITY;
+
+        if t == t0 {
+            return Some(self.last);
+        }
+
+        // Rounding of the clipped step is ignored
+        let eps = 4.0 * f64::EPSILON * t.abs().max(1.0);
+
+        let mut current = self.solver.current();
+        let dense = self.solver.has_dense();
+
+        let mut result = loop {
+            let tc = current.as_ref()[0];
+
+            if (tc - t).abs() <= eps {
+                break current;
+            }
+
+            if tc > t {
+                match self.solver.dense(t) {
+                    Some(result) => break result,
+                    None => {
+                        self.finished = true;
+                        return None;
+                    }
+                }
+            }
+
+            if !dense {
+                self.solver.clip(t - tc);
+            }
+
+            current = match self.solver.next() {
+                Some(next) => next,
+                None => {
+                    self.finished = true;
+                    return None;
+                }
+            };
+        };
+        result.as_mut()[0] = t;
+        self.last = result;
+
+        Some(result)
+    }
+}
+
+impl<I, J, T> Solver for Output<I, J, T>
+where
+    I: Solver<Item = T>,
+    J: Iterator<Item = f64>,
+    T: Copy + AsRef<[f64]> + AsMut<[f64]>,
+{
+    fn stats(&self) -> Stats {
+        self.solver.stats()
+    }
+
+    fn current(&self) -> T {
+        self.last
+    }
+
+    /// The next value is returned at most `h` after the last one,
+    /// the next time is kept for the following value
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_output() {
+    use crate::twobody::{max_error, test_system, State};
+
+    let system = test_system();
+    let times: Vec<_> = grid(0.0, 0.37, 30).collect();
+
+    // Values are exactly at `times` and as accurate as the solver
+    let check = |solver: &mut dyn Solver<Item = State<2>>, tol: f64| {
+        let values: Vec<_> = system.output(solver, times.clone()).collect();
+        assert_eq!(values.len(), times.len());
+
+        let mut max = 0.0f64;
+        for (state, &t) in values.iter().zip(&times) {
+            assert_eq!(state[0], t);
+            max = max.max(max_error(&system, *state));
+        }
+        assert!(max < tol);
+    };
+
+    check(&mut system.construct_rk4(0.01), 1e-7);
+    check(&mut system.construct_rk45(0.01, 1e-10, f64::INFINITY), 5e-7);
+    check(
+        &mut system.construct_dopri5(0.01, 1e-10, f64::INFINITY),
+        5e-9,
+    );
+    check(&mut system.construct_gbs(0.01, 1e-12, f64::INFINITY), 5e-10);
+    check(
+        &mut system.construct_rkn64(0.01, 1e-12, f64::INFINITY),
+        1e-12,
+    );
+    check(&mut system.construct_gauss_legendre(0.01, 2), 1e-7);
+    check(&mut system.construct_abm(0.01, 4), 1e-5);
+    check(&mut system.construct_verlet(0.001), 2e-5);
+    check(&mut system.construct_kepler(0.5).unwrap(), 1e-12);
+    check(&mut system.construct_universal(0.5).unwrap(), 1e-12);
+
+    // Dense output of the solver needs no extra calls
+    let mut output = system.output(
+        system.construct_dopri5(0.01, 1e-10, f64::INFINITY),
+        times.clone(),
+    );
+    output.by_ref().count();
+    let stats = output.stats();
+    assert!(stats.rhs_calls <= 6 * stats.accepted + 1);
+
+    // Steps of the solver with dense output are not clipped,
+    // even before its first step
+    let mut dopri5 = system.construct_dopri5(0.01, 1e-10, f64::INFINITY);
+    system.output(&mut dopri5, times.clone()).count();
+    let mut free = system.construct_dopri5(0.01, 1e-10, f64::INFINITY);
+    let steps = free.position(|state| state[0] >= times[29]).unwrap() + 1;
+    assert_eq!(dopri5.stats().accepted, steps);
+
+    // Irregular times, the init time and repeated times
+    let times = [0.0, 0.0, 0.123, 0.123, 0.5, 2.0, 2.001];
+    let values: Vec<_> = system.output(system.construct_rk4(0.1), times).collect();
+    assert_eq!(values.len(), times.len());
+    assert_eq!(values[0].data, system.get_init().data);
+    for (state, &t) in values.iter().zip(&times) {
+        assert_eq!(state[0], t);
+    }
+    assert_eq!(values[2].data, values[3].data);
+
+    // Output ends at the first time out of order
+    let times = [0.5, 0.2, 0.7];
+    let mut output = system.output(system.construct_rk4(0.1), times);
+    assert_eq!(output.by_ref().count(), 1);
+    assert!(output.next().is_none());
+
+    // Output starts from the current value of the solver
+    let mut solver = system.construct_rk4(0.1);
+    let current = solver.by_ref().nth(4).unwrap();
+    let values: Vec<_> = system.output(solver, [0.5, 0.75]).collect();
+    assert_eq!(values[0].data, current.data);
+    assert_eq!(values[1][0], 0.75);
+
+    let mut solver = system.construct_rk4(0.1);
+    solver.by_ref().nth(4);
+    assert_eq!(system.output(solver, [0.0, 0.75]).count(), 0);
+
+    // Output ends with the solver
+    let solver = system.terminate(system.construct_rk4(0.1)).end(1.0);
+    let values: Vec<_> = system.output(solver, grid(0.0, 0.3, 10)).collect();
+    assert_eq!(values.len(), 4);
+
+    // Clipped output returns the value at the end time,
+    // the next time is kept
+    let output = system.output(
+        system.construct_gbs(0.01, 1e-12, f64::INFINITY),
+        grid(0.0, 0.3, 10),
+    );
+    let mut solver = system.terminate(output).end(1.0);
+    let values: Vec<State<2>> = solver.by_ref().collect();
+    let times: Vec<_> = values.iter().map(|state| state[0]).collect();
+    let expected: Vec<_> = grid(0.0, 0.3, 4).chain(Some(1.0)).collect();
+    assert_eq!(times, expected);
+    assert!(solver.reached());
+    assert!(max_error(&system, values[4]) < 1e-10);
+}
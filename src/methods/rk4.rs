@@ -1,56 +1,50 @@
-use crate::soe::Soe;
+use super::{explicit_rk::ExplicitRk, tableau::RK4, Solver, Stats};
 
-use std::{
-    fmt::Debug,
-    ops::{Div, Index, IndexMut, Mul},
-};
-use std::{iter::Iterator, ops::Add};
+use std::iter::Iterator;
 
-use super::call_soe;
-
-pub struct Rk4<T, S> {
-    init: T,
-    soe: S,
-    h: f64,
-}
+/// Classical Runge–Kutta method, 4th order
+///
+/// `ExplicitRk` with `RK4` tableau
+pub struct Rk4<T, S>(ExplicitRk<T, S>);
 
 impl<T, S> Rk4<T, S> {
     pub fn new(init: T, soe: S, h: f64) -> Self {
-        Self { init, soe, h }
+        Self(ExplicitRk::new(init, soe, h, RK4))
     }
 }
 
 impl<T, S> Iterator for Rk4<T, S>
 where
-    S: Soe<Args = T>,
-    T: Default
-        + Copy
-        + AsMut<[f64]>
-        + Add<T, Output = T>
-        + Mul<f64, Output = T>
-        + Div<f64, Output = T>,
+    ExplicitRk<T, S>: Iterator<Item = T>,
 {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let k1 = call_soe(&mut self.soe, &self.init);
-
-        let next_params = self.init + k1 * self.h / 2.0;
-
-        let k2 = call_soe(&mut self.soe, &next_params);
-
-        let next_params = self.init + k2 * self.h / 2.0;
-
-        let k3 = call_soe(&mut self.soe, &next_params);
+        self.0.next()
+    }
+}
 
-        let next_params = self.init + k3 * self.h;
+impl<T, S> Solver for Rk4<T, S>
+where
+    ExplicitRk<T, S>: Solver<Item = T>,
+{
+    fn stats(&self) -> Stats {
+        self.0.stats()
+    }
 
-        let k4 = call_soe(&mut self.soe, &next_params);
+    fn current(&self) -> T {
+        self.0.current()
+    }
 
-        let next_step = self.init + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * self.h / 6.0;
+    fn clip(&mut self, h: f64) {
+        self.0.clip(h);
+    }
 
-        self.init = next_step;
+    fn dense(&self, t: f64) -> Option<T> {
+        self.0.dense(t)
+    }
 
-        Some(next_step)
+    fn has_dense(&self) -> bool {
+        self.0.has_dense()
     }
 }
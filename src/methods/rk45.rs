@@ -1,107 +1,78 @@
-use super::{abs, call_soe};
-use crate::soe::Soe;
+use super::{
+    controller::{Controller, Integral},
+    explicit_rk::ExplicitRk,
+    tableau::FEHLBERG,
+    Adaptive, Control, Solver, Stats, Tolerance,
+};
+
 use std::iter::Iterator;
-use std::ops::{Add, Div, Mul, Sub};
-use crate::kepler::Kepler;
-use crate::vector::Vector5;
 
-pub struct Rk45<T, S> {
-    init: T,
-    soe: S,
-    h: f64,
-    e: f64,
-    max: f64,
-}
+/// Runge–Kutta–Fehlberg 4(5) method, the solution is 4th order
+///
+/// `ExplicitRk` with `FEHLBERG` tableau in adaptive mode
+pub struct Rk45<T, S, C = Integral>(ExplicitRk<T, S, C>);
 
 impl<T, S> Rk45<T, S> {
-    pub fn new(init: T, soe: S, h: f64, e: f64, max: f64) -> Self {
-        Self {
-            init,
-            soe,
-            h,
-            e,
-            max,
-        }
+    /// Create method with init `h` step, `tol` tolerance and `max` time
+    pub fn new(init: T, soe: S, h: f64, tol: impl Into<Tolerance>, max: f64) -> Self {
+        Self(ExplicitRk::new(init, soe, h, FEHLBERG).adaptive(tol, max))
     }
 }
 
-impl<T, S> Iterator for Rk45<T, S>
-where
-    S: Soe<Args = T>,
-    T: Default
-        + Copy
-        + AsMut<[f64]>
-        + AsRef<[f64]>
-        + Add<T, Output = T>
-        + Mul<f64, Output = T>
-        + Div<f64, Output = T>
-        + Sub<T, Output = T>,
-{
-    type Item = T;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.init.as_ref()[0] > self.max {
-            return None;
-        }
-
-        let k1 = call_soe(&mut self.soe, &self.init) * self.h;
-
-        let next_params = self.init + k1 / 4.0;
-
-        let k2 = call_soe(&mut self.soe, &next_params) * self.h;
-
-        let mut next_params = self.init + k1 * 3.0 / 32.0 + k2 * 9.0 / 32.0;
-
-        next_params.as_mut()[0] = self.init.as_ref()[0] + self.h * 3.0 / 8.0;
-
-        let k3 = call_soe(&mut self.soe, &next_params) * self.h;
-
-        let mut next_params =
-            self.init + (k1 * 1932.0 / 2197.0) - (k2 * 7200.0 / 2197.0) + (k3 * 7296.0 / 2197.0);
-
-        next_params.as_mut()[0] = self.init.as_ref()[0] + self.h * 12.0 / 13.0;
-
-        let k4 = call_soe(&mut self.soe, &next_params) * self.h;
-
-        let mut next_params = self.init + (k1 * 439.0 / 216.0) - (k2 * 8.0) + (k3 * 3680.0 / 513.0)
-            - (k4 * 845.0 / 4104.0);
-
-        next_params.as_mut()[0] = self.init.as_ref()[0] + self.h;
-
-        let k5 = call_soe(&mut self.soe, &next_params) * self.h;
+impl<T, S, C> Rk45<T, S, C> {
+    /// Set step size controller, `Integral` by default
+    pub fn controller<C2: Controller>(self, controller: C2) -> Rk45<T, S, C2> {
+        Rk45(self.0.controller(controller))
+    }
 
-        let mut next_params = self.init - (k1 * 8.0 / 27.0) + (k2 * 2.0) - (k3 * 3544.0 / 2565.0)
-            + (k4 * 1859.0 / 4104.0)
-            - (k5 * 11.0 / 40.0);
+    /// Step size of the next attempt
+    pub fn h(&self) -> f64 {
+        self.0.h()
+    }
+}
 
-        next_params.as_mut()[0] = self.init.as_ref()[0] + self.h / 2.0;
+impl<T, S, C> Adaptive for Rk45<T, S, C> {
+    fn control(&self) -> &Control {
+        self.0.control()
+    }
 
-        let k6 = call_soe(&mut self.soe, &next_params) * self.h;
+    fn control_mut(&mut self) -> &mut Control {
+        self.0.control_mut()
+    }
+}
 
-        let mut next =
-            self.init + k1 * 25.0 / 216.0 + k3 * 1408.0 / 2565.0 + k4 * 2197.0 / 4104.0 - k5 / 5.0;
+impl<T, S, C> Iterator for Rk45<T, S, C>
+where
+    ExplicitRk<T, S, C>: Iterator<Item = T>,
+{
+    type Item = T;
 
-        next.as_mut()[0] = 0.0;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
 
-        let mut next_cap =
-            self.init + k1 * 16.0 / 135.0 + k3 * 6656.0 / 12825.0 + k4 * 28561.0 / 56430.0
-                - k5 * 9.0 / 50.0
-                + k6 * 2.0 / 55.0;
+impl<T, S, C> Solver for Rk45<T, S, C>
+where
+    ExplicitRk<T, S, C>: Solver<Item = T>,
+{
+    fn stats(&self) -> Stats {
+        self.0.stats()
+    }
 
-        next_cap.as_mut()[0] = 0.0;
+    fn current(&self) -> T {
+        self.0.current()
+    }
 
-        let r = abs(&(next_cap - next));
+    fn clip(&mut self, h: f64) {
+        self.0.clip(h);
+    }
 
-        let sigma = (self.e / r).powf(0.2) * 0.9 ;
+    fn dense(&self, t: f64) -> Option<T> {
+        self.0.dense(t)
+    }
 
-        if r <= self.e {
-            next.as_mut()[0] = self.init.as_ref()[0] + self.h;
-            self.init = next;
-            self.h *= sigma;
-            Some(next)
-        } else {
-            self.h *= sigma;
-            self.next()
-        }
+    fn has_dense(&self) -> bool {
+        self.0.has_dense()
     }
 }
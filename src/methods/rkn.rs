@@ -0,0 +1,476 @@
+use super::{
+    controller::{Controller, Integral},
+    next_step_size, Adaptive, Control, Solver, Stats, StepRecord, Tolerance,
+};
+use crate::soe::SplitSoe;
+
+use std::iter::Iterator;
+use std::ops::{Add, Mul, Sub};
+
+/// Butcher tableau of Runge–Kutta–Nyström method for `r'' = g(r)`
+///
+/// Stages are `g(r + c[i] * h * v + h^2 * sum(a[i][j] * g[j]))`,
+/// the solution is `r + h * v + h^2 * sum(b[i] * g[i])` and
+/// `v + h * sum(bp[i] * g[i])`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RknTableau {
+    /// Nodes of stages
+    pub c: &'static [f64],
+    /// Coefficients of stages from the 2nd one,
+    /// row `i` has `i + 1` items
+    pub a: &'static [&'static [f64]],
+    /// Weights of positions
+    pub b: &'static [f64],
+    /// Weights of velocities
+    pub bp: &'static [f64],
+    /// Weights of positions and velocities of the embedded
+    /// solution which is used to estimate error of the solution
+    pub embedded: Option<(&'static [f64], &'static [f64])>,
+    /// Order of the solution
+    pub order: i32,
+    /// Order of the embedded solution
+    pub embedded_order: i32,
+}
+
+impl RknTableau {
+    /// Number of stages
+    pub fn stages(&self) -> usize {
+        self.c.len()
+    }
+
+    /// The last stage is evaluated in the positions of the solution,
+    /// so it is the first stage of the next step (FSAL)
+    pub fn is_fsal(&self) -> bool {
+        match self.a.last() {
+            Some(last) => {
+                last.len() + 1 == self.b.len()
+                    && self.c[last.len()] == 1.0
+                    && self.b[last.len()] == 0.0
+                    && last.iter().zip(self.b).all(|(a, b)| a == b)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Classical Runge–Kutta–Nyström method, 4th order with 3 stages
+pub const RKN4: RknTableau = RknTableau {
+    c: &[0.0, 1.0 / 2.0, 1.0],
+    a: &[&[1.0 / 8.0], &[0.0, 1.0 / 2.0]],
+    b: &[1.0 / 6.0, 1.0 / 3.0, 0.0],
+    bp: &[1.0 / 6.0, 2.0 / 3.0, 1.0 / 6.0],
+    embedded: None,
+    order: 4,
+    embedded_order: 0,
+};
+
+/// Dormand–El-Mikkawy–Prince RKN6(4)6FM method
+pub const RKN64: RknTableau = RknTableau {
+    c: &[0.0, 1.0 / 10.0, 3.0 / 10.0, 7.0 / 10.0, 17.0 / 25.0, 1.0],
+    a: &[
+        &[1.0 / 200.0],
+        &[-1.0 / 2200.0, 1.0 / 22.0],
+        &[637.0 / 6600.0, -7.0 / 110.0, 7.0 / 33.0],
+        &[
+            225437.0 / 1968750.0,
+            -30073.0 / 281250.0,
+            65569.0 / 281250.0,
+            -9367.0 / 984375.0,
+        ],
+        &[
+            151.0 / 2142.0,
+            5.0 / 116.0,
+            385.0 / 1368.0,
+            55.0 / 168.0,
+            -6250.0 / 28101.0,
+        ],
+    ],
+    b: &[
+        151.0 / 2142.0,
+        5.0 / 116.0,
+        385.0 / 1368.0,
+        55.0 / 168.0,
+        -6250.0 / 28101.0,
+        0.0,
+    ],
+    bp: &[
+        151.0 / 2142.0,
+        25.0 / 522.0,
+        275.0 / 684.0,
+        275.0 / 252.0,
+        -78125.0 / 112404.0,
+        1.0 / 12.0,
+    ],
+    embedded: Some((
+        &[
+            1349.0 / 157500.0,
+            7873.0 / 50000.0,
+            192199.0 / 900000.0,
+            521683.0 / 2100000.0,
+            -16.0 / 125.0,
+            0.0,
+        ],
+        &[
+            1349.0 / 157500.0,
+            7873.0 / 45000.0,
+            27457.0 / 90000.0,
+            521683.0 / 630000.0,
+            -2.0 / 5.0,
+            1.0 / 12.0,
+        ],
+    )),
+    order: 6,
+    embedded_order: 4,
+};
+
+/// Sum of accelerations `g` with `weights`
+fn combine<T>(weights: &[f64], g: &[T]) -> T
+where
+    T: Default + Copy + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    let mut result = T::default();
+
+    for (&w, &g) in weights.iter().zip(g) {
+        if w != 0.0 {
+            result = result + g * w;
+        }
+    }
+
+    result
+}
+
+/// Acceleration in `args`, only derivatives of velocities are set
+fn accel<T, S>(soe: &mut S, args: &T, stats: &mut Stats) -> T
+where
+    S: SplitSoe<Args = T>,
+{
+    stats.rhs_calls += 1;
+    soe.call_f2(args)
+}
+
+/// Move velocities of `args` to positions, other values are zero
+///
+/// State is `[t, positions, velocities]` with the same number
+/// of positions and velocities
+fn to_positions<T>(args: &T) -> T
+where
+    T: Default + AsRef<[f64]> + AsMut<[f64]>,
+{
+    let args = args.as_ref();
+    let n = (args.len() - 1) / 2;
+
+    let mut result = T::default();
+    result.as_mut()[1..=n].copy_from_slice(&args[n + 1..]);
+    result
+}
+
+/// Runge–Kutta–Nyström method with `RknTableau`
+///
+/// Second order system `r'' = g(r)` is integrated directly:
+/// `call_f2` gives the acceleration, positions are moved with
+/// the velocities of the state (`r' = v`), so `call_f1` is not used.
+///
+/// Step is fixed by default. Tableau with embedded solution
+/// can be used with step size control, see `adaptive`.
+/// The last stage of FSAL tableau is reused in the next step
+pub struct Rkn<T, S, C = Integral> {
+    init: T,
+    soe: S,
+    h: f64,
+    tableau: RknTableau,
+    // Accelerations in stages of the last step
+    g: Vec<T>,
+    // The first stage of the next step for FSAL tableau
+    fsal: Option<T>,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    // Tolerance of the adaptive mode
+    tol: Option<Tolerance>,
+    max: f64,
+    control: Control,
+    controller: C,
+}
+
+impl<T, S> Rkn<T, S> {
+    /// Create method with `tableau` and fixed `h` step
+    pub fn new(init: T, soe: S, h: f64, tableau: RknTableau) -> Self {
+        Self {
+            init,
+            soe,
+            h,
+            tableau,
+            g: Vec::with_capacity(tableau.stages()),
+            fsal: None,
+            clip: f64::INFINITY,
+            tol: None,
+            max: f64::INFINITY,
+            control: Control::default(),
+            controller: Integral::default(),
+        }
+    }
+}
+
+impl<T, S, C> Rkn<T, S, C> {
+    /// Control step size with `tol` tolerance using the embedded
+    /// solution, iterator stops after `max` time
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tableau has no embedded solution
+    pub fn adaptive(mut self, tol: impl Into<Tolerance>, max: f64) -> Self {
+        assert!(
+            self.tableau.embedded.is_some(),
+            "tableau has no embedded solution"
+        );
+
+        self.tol = Some(tol.into());
+        self.max = max;
+        self
+    }
+
+    /// Set step size controller, `Integral` by default
+    pub fn controller<C2: Controller>(self, controller: C2) -> Rkn<T, S, C2> {
+        Rkn {
+            init: self.init,
+            soe: self.soe,
+            h: self.h,
+            tableau: self.tableau,
+            g: self.g,
+            fsal: self.fsal,
+            clip: self.clip,
+            tol: self.tol,
+            max: self.max,
+            control: self.control,
+            controller,
+        }
+    }
+
+    /// Step size of the next attempt
+    pub fn h(&self) -> f64 {
+        self.h
+    }
+
+    /// Tableau of the method
+    pub fn tableau(&self) -> &RknTableau {
+        &self.tableau
+    }
+}
+
+impl<T, S, C> Adaptive for Rkn<T, S, C> {
+    fn control(&self) -> &Control {
+        &self.control
+    }
+
+    fn control_mut(&mut self) -> &mut Control {
+        &mut self.control
+    }
+}
+
+impl<T, S, C> Iterator for Rkn<T, S, C>
+where
+    C: Controller,
+    S: SplitSoe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.init.as_ref()[0];
+
+        if t > self.max || self.control.error.is_some() {
+            return None;
+        }
+
+        let g1 = match self.fsal.take() {
+            Some(g1) => g1,
+            None => accel(&mut self.soe, &self.init, &mut self.control.stats),
+        };
+
+        // Derivatives of time and positions
+        let mut drift = to_positions(&self.init);
+        drift.as_mut()[0] = 1.0;
+
+        let mut rejections = 0;
+
+        loop {
+            if self.tol.is_some() {
+                self.h = self.control.step_size(t, self.h)?;
+            }
+
+            let h = self.h.min(self.clip);
+
+            self.g.clear();
+            self.g.push(g1);
+
+            for (row, &c) in self.tableau.a.iter().zip(&self.tableau.c[1..]) {
+                let shift = to_positions(&combine(row, &self.g));
+                let args = self.init + drift * (c * h) + shift * (h * h);
+                let g = accel(&mut self.soe, &args, &mut self.control.stats);
+                self.g.push(g);
+            }
+
+            let shift = to_positions(&combine(self.tableau.b, &self.g));
+            let mut next =
+                self.init + drift * h + shift * (h * h) + combine(self.tableau.bp, &self.g) * h;
+            next.as_mut()[0] = t + h;
+
+            let accepted = match (&self.tol, self.tableau.embedded) {
+                (Some(tol), Some((b, bp))) => {
+                    let shift = to_positions(&combine(b, &self.g));
+                    let embedded =
+                        self.init + drift * h + shift * (h * h) + combine(bp, &self.g) * h;
+
+                    let mut error = next - embedded;
+                    error.as_mut()[0] = 0.0;
+
+                    let r = tol.norm(&error, &self.init, &next);
+                    let order = self.tableau.order.min(self.tableau.embedded_order) + 1;
+
+                    let factor = self.controller.factor(r, order);
+                    self.h = next_step_size(self.h, h, h * factor, r <= 1.0);
+
+                    self.control.record(StepRecord {
+                        t,
+                        h,
+                        error: r,
+                        accepted: r <= 1.0,
+                    });
+
+                    r <= 1.0
+                }
+                _ => true,
+            };
+
+            if accepted {
+                if self.tableau.is_fsal() {
+                    self.fsal = self.g.last().copied();
+                }
+
+                self.control.stats.accepted += 1;
+                self.init = next;
+                self.clip = f64::INFINITY;
+
+                return Some(next);
+            }
+
+            rejections += 1;
+            self.control.reject(t, rejections)?;
+        }
+    }
+}
+
+impl<T, S, C> Solver for Rkn<T, S, C>
+where
+    C: Controller,
+    S: SplitSoe<Args = T>,
+    T: Default
+        + Copy
+        + AsMut<[f64]>
+        + AsRef<[f64]>
+        + Add<T, Output = T>
+        + Mul<f64, Output = T>
+        + Sub<T, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.control.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_rkn_tableaux() {
+    for tableau in &[RKN4, RKN64] {
+        assert_eq!(tableau.a.len() + 1, tableau.stages());
+        assert_eq!(tableau.b.len(), tableau.stages());
+        assert_eq!(tableau.bp.len(), tableau.stages());
+
+        // Positions of stages are consistent with nodes
+        for (row, &c) in tableau.a.iter().zip(&tableau.c[1..]) {
+            assert!((row.iter().sum::<f64>() - c * c / 2.0).abs() < 1e-14);
+        }
+
+        let weights = tableau
+            .embedded
+            .into_iter()
+            .chain(Some((tableau.b, tableau.bp)));
+        for (b, bp) in weights {
+            assert!((b.iter().sum::<f64>() - 0.5).abs() < 1e-14);
+            assert!((bp.iter().sum::<f64>() - 1.0).abs() < 1e-14);
+        }
+    }
+
+    assert!(RKN64.is_fsal());
+    assert!(!RKN4.is_fsal());
+}
+
+#[test]
+fn test_rkn() {
+    use crate::soe::Soe;
+    use crate::twobody::{max_error, observed_order, test_system};
+    use crate::vector::Vector;
+
+    let system = test_system();
+
+    // 4th order with 3 evaluations of acceleration per step
+    let order = observed_order(&system, |h| system.construct_rkn4(h), 0.02, 2.0);
+    assert!(order > 3.5);
+
+    let mut rkn4 = system.construct_rkn4(0.02);
+    rkn4.by_ref().take(100).count();
+    assert_eq!(rkn4.stats().rhs_calls, 3 * 100);
+
+    // Adaptive method is more accurate and cheaper than dopri5
+    let mut rkn64 = system.construct_rkn64(0.1, 1e-9, 50.0);
+    let rkn64_error = max_error(&system, rkn64.by_ref().last().unwrap());
+    let mut dopri5 = system.construct_dopri5(0.1, 1e-9, 50.0);
+    let dopri5_error = max_error(&system, dopri5.by_ref().last().unwrap());
+
+    assert!(rkn64.error().is_none());
+    assert!(rkn64_error < 1e-7);
+    assert!(rkn64_error < dopri5_error / 5.0);
+    assert!(rkn64.stats().rhs_calls < dopri5.stats().rhs_calls);
+
+    // The last stage is reused in the next step
+    let stats = rkn64.stats();
+    assert_eq!(stats.rhs_calls, 1 + 5 * (stats.accepted + stats.rejected));
+
+    // Positions are moved with the velocities of the state,
+    // only the acceleration is evaluated
+    struct Oscillator;
+
+    impl Soe for Oscillator {
+        type Args = Vector<f64, 3>;
+
+        fn call(&mut self, _: &Self::Args) -> Self::Args {
+            unreachable!()
+        }
+    }
+
+    impl SplitSoe for Oscillator {
+        fn call_f1(&mut self, _: &Self::Args) -> Self::Args {
+            unreachable!()
+        }
+
+        fn call_f2(&mut self, args: &Self::Args) -> Self::Args {
+            [0.0, 0.0, -args[1]].into()
+        }
+    }
+
+    let init: Vector<f64, 3> = [0.0, 1.0, 0.0].into();
+    let last = Rkn::new(init, Oscillator, 0.01, RKN64).nth(99).unwrap();
+    assert!((last[1] - 1.0f64.cos()).abs() < 1e-10);
+    assert!((last[2] + 1.0f64.sin()).abs() < 1e-10);
+}
@@ -0,0 +1,162 @@
+use super::{Solver, Stats};
+use crate::soe::SplitSoe;
+
+use std::{
+    iter::Iterator,
+    ops::{Add, Mul},
+};
+
+/// Weights of Störmer–Verlet (leapfrog) method, 2nd order
+pub const VERLET: [f64; 1] = [1.0];
+
+/// Weights of Forest–Ruth (Yoshida) method, 4th order
+///
+/// w1 = 1 / (2 - 2^(1/3)), w0 = -2^(1/3) / (2 - 2^(1/3))
+pub const YOSHIDA4: [f64; 3] = [
+    1.351_207_191_959_657_8,
+    -1.702_414_383_919_315_3,
+    1.351_207_191_959_657_8,
+];
+
+/// Weights of Yoshida method, 6th order (solution A)
+pub const YOSHIDA6: [f64; 7] = [
+    0.784_513_610_477_560,
+    0.235_573_213_359_357,
+    -1.177_679_984_178_87,
+    1.315_186_320_683_906,
+    -1.177_679_984_178_87,
+    0.235_573_213_359_357,
+    0.784_513_610_477_560,
+];
+
+/// Symplectic method: composition of velocity Verlet steps
+/// (kick - drift - kick) with steps `h * weights[i]`
+///
+/// Energy error of symplectic method is bounded, so it
+/// does not drift on long runs
+///
+/// Evaluations of `f2` are counted as right-hand side calls in stats,
+/// `f1` usually just returns velocities
+pub struct Symplectic<T, S> {
+    init: T,
+    soe: S,
+    h: f64,
+    // Max size of the next step, see `Solver::clip`
+    clip: f64,
+    weights: &'static [f64],
+    // Derivative of velocities in `init`
+    kick: Option<T>,
+    stats: Stats,
+}
+
+impl<T, S> Symplectic<T, S> {
+    pub fn new(init: T, soe: S, h: f64, weights: &'static [f64]) -> Self {
+        Self {
+            init,
+            soe,
+            h,
+            clip: f64::INFINITY,
+            weights,
+            kick: None,
+            stats: Stats::default(),
+        }
+    }
+}
+
+impl<T, S> Iterator for Symplectic<T, S>
+where
+    S: SplitSoe<Args = T>,
+    T: Default + Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut kick = match self.kick {
+            Some(kick) => kick,
+            None => {
+                self.stats.rhs_calls += 1;
+                self.soe.call_f2(&self.init)
+            }
+        };
+
+        let step = self.h.min(self.clip);
+        self.clip = f64::INFINITY;
+
+        for &weight in self.weights {
+            let h = step * weight;
+
+            // Kick
+            self.init = self.init + kick * (h / 2.0);
+
+            // Drift
+            let mut drift = self.soe.call_f1(&self.init);
+            drift.as_mut()[0] = 1.0;
+            self.init = self.init + drift * h;
+
+            // Kick
+            kick = self.soe.call_f2(&self.init);
+            self.stats.rhs_calls += 1;
+            self.init = self.init + kick * (h / 2.0);
+        }
+
+        self.kick = Some(kick);
+        self.stats.accepted += 1;
+
+        Some(self.init)
+    }
+}
+
+impl<T, S> Solver for Symplectic<T, S>
+where
+    S: SplitSoe<Args = T>,
+    T: Default + Copy + AsMut<[f64]> + Add<T, Output = T> + Mul<f64, Output = T>,
+{
+    fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn current(&self) -> T {
+        self.init
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+}
+
+#[test]
+fn test_symplectic() {
+    use crate::twobody::{max_error, test_system};
+
+    let system = test_system();
+    let h = 0.05;
+    let steps = 20000;
+
+    // Energy error of symplectic method is bounded
+    // while rk4 drifts
+    let max_drift = |drift: Vec<f64>| {
+        let (first, last) = drift.split_at(steps / 10);
+        (
+            first.iter().cloned().fold(0.0, f64::max),
+            last.iter().cloned().fold(0.0, f64::max),
+        )
+    };
+
+    let verlet = system.monitor(system.construct_verlet(h));
+    let (verlet_first, verlet_last) =
+        max_drift(verlet.take(steps).map(|(_, d)| d.energy).collect());
+
+    let rk4 = system.monitor(system.construct_rk4(h));
+    let (rk4_first, rk4_last) = max_drift(rk4.take(steps).map(|(_, d)| d.energy).collect());
+
+    assert!(verlet_last < verlet_first * 1.1);
+    assert!(rk4_last > rk4_first * 5.0);
+
+    // Higher order methods are more accurate
+    let verlet = max_error(&system, system.construct_verlet(h).nth(199).unwrap());
+    let yoshida4 = max_error(&system, system.construct_yoshida4(h).nth(199).unwrap());
+    let yoshida6 = max_error(&system, system.construct_yoshida6(h).nth(199).unwrap());
+
+    assert!(yoshida4 < verlet / 10.0);
+    assert!(yoshida6 < yoshida4 / 10.0);
+}
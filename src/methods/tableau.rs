@@ -0,0 +1,352 @@
+/// Butcher tableau of explicit Runge–Kutta method
+///
+/// Nodes `c` are not needed: time is integrated as a component
+/// of the state, so they are sums of the rows of `a`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tableau {
+    /// Coefficients of stages from the 2nd one,
+    /// row `i` has `i + 1` items
+    pub a: &'static [&'static [f64]],
+    /// Weights of the solution
+    pub b: &'static [f64],
+    /// Weights of the embedded solution which is used
+    /// to estimate error of the solution
+    pub embedded: Option<&'static [f64]>,
+    /// Order of the solution
+    pub order: i32,
+    /// Order of the embedded solution
+    pub embedded_order: i32,
+}
+
+impl Tableau {
+    /// Number of stages
+    pub fn stages(&self) -> usize {
+        self.b.len()
+    }
+
+    /// The last stage is evaluated in the solution, so it is
+    /// the first stage of the next step (FSAL)
+    pub fn is_fsal(&self) -> bool {
+        match self.a.last() {
+            Some(last) => {
+                last.len() + 1 == self.b.len()
+                    && self.b[last.len()] == 0.0
+                    && last.iter().zip(self.b).all(|(a, b)| a == b)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Explicit Euler method, 1st order
+pub const EULER: Tableau = Tableau {
+    a: &[],
+    b: &[1.0],
+    embedded: None,
+    order: 1,
+    embedded_order: 0,
+};
+
+/// Explicit midpoint method, 2nd order
+pub const MIDPOINT: Tableau = Tableau {
+    a: &[&[1.0 / 2.0]],
+    b: &[0.0, 1.0],
+    embedded: None,
+    order: 2,
+    embedded_order: 0,
+};
+
+/// Heun's method with embedded Euler method, 2(1)
+pub const HEUN: Tableau = Tableau {
+    a: &[&[1.0]],
+    b: &[1.0 / 2.0, 1.0 / 2.0],
+    embedded: Some(&[1.0, 0.0]),
+    order: 2,
+    embedded_order: 1,
+};
+
+/// Ralston's method, 2nd order with min error bound
+pub const RALSTON: Tableau = Tableau {
+    a: &[&[2.0 / 3.0]],
+    b: &[1.0 / 4.0, 3.0 / 4.0],
+    embedded: None,
+    order: 2,
+    embedded_order: 0,
+};
+
+/// Classical Runge–Kutta method, 4th order
+pub const RK4: Tableau = Tableau {
+    a: &[&[1.0 / 2.0], &[0.0, 1.0 / 2.0], &[0.0, 0.0, 1.0]],
+    b: &[1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0],
+    embedded: None,
+    order: 4,
+    embedded_order: 0,
+};
+
+/// Runge–Kutta 3/8 rule, 4th order
+pub const RK38: Tableau = Tableau {
+    a: &[&[1.0 / 3.0], &[-1.0 / 3.0, 1.0], &[1.0, -1.0, 1.0]],
+    b: &[1.0 / 8.0, 3.0 / 8.0, 3.0 / 8.0, 1.0 / 8.0],
+    embedded: None,
+    order: 4,
+    embedded_order: 0,
+};
+
+/// Runge–Kutta–Fehlberg 4(5) method, the solution is 4th order
+pub const FEHLBERG: Tableau = Tableau {
+    a: &[
+        &[1.0 / 4.0],
+        &[3.0 / 32.0, 9.0 / 32.0],
+        &[1932.0 / 2197.0, -7200.0 / 2197.0, 7296.0 / 2197.0],
+        &[439.0 / 216.0, -8.0, 3680.0 / 513.0, -845.0 / 4104.0],
+        &[
+            -8.0 / 27.0,
+            2.0,
+            -3544.0 / 2565.0,
+            1859.0 / 4104.0,
+            -11.0 / 40.0,
+        ],
+    ],
+    b: &[
+        25.0 / 216.0,
+        0.0,
+        1408.0 / 2565.0,
+        2197.0 / 4104.0,
+        -1.0 / 5.0,
+        0.0,
+    ],
+    embedded: Some(&[
+        16.0 / 135.0,
+        0.0,
+        6656.0 / 12825.0,
+        28561.0 / 56430.0,
+        -9.0 / 50.0,
+        2.0 / 55.0,
+    ]),
+    order: 4,
+    embedded_order: 5,
+};
+
+/// Cash–Karp 5(4) method
+pub const CASH_KARP: Tableau = Tableau {
+    a: &[
+        &[1.0 / 5.0],
+        &[3.0 / 40.0, 9.0 / 40.0],
+        &[3.0 / 10.0, -9.0 / 10.0, 6.0 / 5.0],
+        &[-11.0 / 54.0, 5.0 / 2.0, -70.0 / 27.0, 35.0 / 27.0],
+        &[
+            1631.0 / 55296.0,
+            175.0 / 512.0,
+            575.0 / 13824.0,
+            44275.0 / 110592.0,
+            253.0 / 4096.0,
+        ],
+    ],
+    b: &[
+        37.0 / 378.0,
+        0.0,
+        250.0 / 621.0,
+        125.0 / 594.0,
+        0.0,
+        512.0 / 1771.0,
+    ],
+    embedded: Some(&[
+        2825.0 / 27648.0,
+        0.0,
+        18575.0 / 48384.0,
+        13525.0 / 55296.0,
+        277.0 / 14336.0,
+        1.0 / 4.0,
+    ]),
+    order: 5,
+    embedded_order: 4,
+};
+
+/// Dormand–Prince 5(4) method, see also `Dopri5` with dense output
+pub const DORMAND_PRINCE: Tableau = Tableau {
+    a: &[
+        &[1.0 / 5.0],
+        &[3.0 / 40.0, 9.0 / 40.0],
+        &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+        &[
+            19372.0 / 6561.0,
+            -25360.0 / 2187.0,
+            64448.0 / 6561.0,
+            -212.0 / 729.0,
+        ],
+        &[
+            9017.0 / 3168.0,
+            -355.0 / 33.0,
+            46732.0 / 5247.0,
+            49.0 / 176.0,
+            -5103.0 / 18656.0,
+        ],
+        &[
+            35.0 / 384.0,
+            0.0,
+            500.0 / 1113.0,
+            125.0 / 192.0,
+            -2187.0 / 6784.0,
+            11.0 / 84.0,
+        ],
+    ],
+    b: &[
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+        0.0,
+    ],
+    embedded: Some(&[
+        5179.0 / 57600.0,
+        0.0,
+        7571.0 / 16695.0,
+        393.0 / 640.0,
+        -92097.0 / 339200.0,
+        187.0 / 2100.0,
+        1.0 / 40.0,
+    ]),
+    order: 5,
+    embedded_order: 4,
+};
+
+/// Tsitouras 5(4) method (2011)
+pub const TSITOURAS: Tableau = Tableau {
+    a: &[
+        &[0.161],
+        &[-0.008480655492356989, 0.335480655492357],
+        &[2.897153057105493, -6.359448489975075, 4.3622954328695815],
+        &[
+            5.325864828439257,
+            -11.748883564062828,
+            7.4955393428898365,
+            -0.09249506636175525,
+        ],
+        &[
+            5.86145544294642,
+            -12.92096931784711,
+            8.159367898576159,
+            -0.071584973281401,
+            -0.028269050394068383,
+        ],
+        &[
+            0.09646076681806523,
+            0.01,
+            0.4798896504144996,
+            1.379008574103742,
+            -3.290069515436081,
+            2.324710524099774,
+        ],
+    ],
+    b: &[
+        0.09646076681806523,
+        0.01,
+        0.4798896504144996,
+        1.379008574103742,
+        -3.290069515436081,
+        2.324710524099774,
+        0.0,
+    ],
+    embedded: Some(&[
+        0.09646076681806523 + 0.0017800110522257772,
+        0.01 + 0.0008164344596567469,
+        0.4798896504144996 - 0.007880878010261995,
+        1.379008574103742 + 0.1447110071732629,
+        -3.290069515436081 - 0.5823571654525552,
+        2.324710524099774 + 0.45808210592918697,
+        -1.0 / 66.0,
+    ]),
+    order: 5,
+    embedded_order: 4,
+};
+
+/// Verner 6(5) method (DVERK)
+pub const VERNER: Tableau = Tableau {
+    a: &[
+        &[1.0 / 6.0],
+        &[4.0 / 75.0, 16.0 / 75.0],
+        &[5.0 / 6.0, -8.0 / 3.0, 5.0 / 2.0],
+        &[-165.0 / 64.0, 55.0 / 6.0, -425.0 / 64.0, 85.0 / 96.0],
+        &[12.0 / 5.0, -8.0, 4015.0 / 612.0, -11.0 / 36.0, 88.0 / 255.0],
+        &[
+            -8263.0 / 15000.0,
+            124.0 / 75.0,
+            -643.0 / 680.0,
+            -81.0 / 250.0,
+            2484.0 / 10625.0,
+            0.0,
+        ],
+        &[
+            3501.0 / 1720.0,
+            -300.0 / 43.0,
+            297275.0 / 52632.0,
+            -319.0 / 2322.0,
+            24068.0 / 84065.0,
+            0.0,
+            3850.0 / 26703.0,
+        ],
+    ],
+    b: &[
+        3.0 / 40.0,
+        0.0,
+        875.0 / 2244.0,
+        23.0 / 72.0,
+        264.0 / 1955.0,
+        0.0,
+        125.0 / 11592.0,
+        43.0 / 616.0,
+    ],
+    embedded: Some(&[
+        13.0 / 160.0,
+        0.0,
+        2375.0 / 5984.0,
+        5.0 / 16.0,
+        12.0 / 85.0,
+        3.0 / 44.0,
+        0.0,
+        0.0,
+    ]),
+    order: 6,
+    embedded_order: 5,
+};
+
+/// All the tableaux above
+pub const ALL: [Tableau; 11] = [
+    EULER,
+    MIDPOINT,
+    HEUN,
+    RALSTON,
+    RK4,
+    RK38,
+    FEHLBERG,
+    CASH_KARP,
+    DORMAND_PRINCE,
+    TSITOURAS,
+    VERNER,
+];
+
+#[test]
+fn test_tableaux() {
+    for tableau in &ALL {
+        assert_eq!(tableau.a.len() + 1, tableau.stages());
+
+        for (i, row) in tableau.a.iter().enumerate() {
+            assert_eq!(row.len(), i + 1);
+        }
+
+        // Consistency: the solutions are exact for a constant derivative
+        assert!((tableau.b.iter().sum::<f64>() - 1.0).abs() < 1e-14);
+
+        if let Some(embedded) = tableau.embedded {
+            assert_eq!(embedded.len(), tableau.stages());
+            assert!((embedded.iter().sum::<f64>() - 1.0).abs() < 1e-14);
+        }
+    }
+
+    assert!(DORMAND_PRINCE.is_fsal());
+    assert!(TSITOURAS.is_fsal());
+    assert!(!VERNER.is_fsal());
+    assert!(!RK4.is_fsal());
+}
@@ -0,0 +1,269 @@
+use super::{Solver, Stats};
+
+use std::iter::Iterator;
+
+/// Iterator adapter which stops the wrapped solver at the end time
+/// or after the max number of steps
+///
+/// The step which crosses the end time is clipped with `Solver::clip`,
+/// so the last value lands exactly on it. Nothing is returned if
+/// the solver is already at (or after) the end time. Solver wrapped in `Events`
+/// also stops at terminal events.
+///
+/// Should be created with `TwoBodySystem::terminate`
+pub struct Terminate<I> {
+    solver: I,
+    // Time of the last value
+    t: f64,
+    t_end: f64,
+    max_steps: usize,
+    steps: usize,
+    finished: bool,
+}
+
+impl<I> Terminate<I> {
+    /// Wrap `solver` from its current value
+    pub fn new(solver: I) -> Self
+    where
+        I: Solver,
+        I::Item: AsRef<[f64]>,
+    {
+        Self {
+            t: solver.current().as_ref()[0],
+            solver,
+            t_end: f64::INFINITY,
+            max_steps: usize::MAX,
+            steps: 0,
+            finished: false,
+        }
+    }
+
+    /// Stop exactly at `t_end` time
+    pub fn end(mut self, t_end: f64) -> Self {
+        self.t_end = t_end;
+        self
+    }
+
+    /// Stop after `max_steps` steps
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// The end time is reached
+    pub fn reached(&self) -> bool {
+        self.finished
+    }
+
+    /// Number of steps made so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+impl<I, T> Iterator for Terminate<I>
+where
+    I: Solver<Item = T>,
+    T: AsRef<[f64]> + AsMut<[f64]>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.steps >= self.max_steps {
+            return None;
+        }
+
+        let bounded = self.t_end.is_finite();
+
+        // Rounding of the clipped step is ignored
+        let eps = 4.0 * f64::EPSILON * self.t_end.abs().max(1.0);
+
+        if bounded {
+            if self.t_end - self.t <= eps {
+                self.finished = true;
+                return None;
+            }
+
+            self.solver.clip(self.t_end - self.t);
+        }
+
+        let mut next = self.solver.next()?;
+        self.steps += 1;
+
+        let t = next.as_ref()[0];
+
+        if bounded && self.t_end - t <= eps {
+            if (t - self.t_end).abs() <= eps {
+                next.as_mut()[0] = self.t_end;
+            }
+
+            self.finished = true;
+        }
+
+        self.t = next.as_ref()[0];
+
+        Some(next)
+    }
+}
+
+impl<I, T> Solver for Terminate<I>
+where
+    I: Solver<Item = T>,
+    T: AsRef<[f64]> + AsMut<[f64]>,
+{
+    fn stats(&self) -> Stats {
+        self.solver.stats()
+    }
+
+    fn current(&self) -> T {
+        // Time of the last value may be snapped to the end time
+        let mut current = self.solver.current();
+        current.as_mut()[0] = self.t;
+        current
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.solver.clip(h);
+    }
+
+    fn dense(&self, t: f64) -> Option<T> {
+        self.solver.dense(t)
+    }
+
+    fn has_dense(&self) -> bool {
+        self.solver.has_dense()
+    }
+}
+
+#[test]
+fn test_terminate() {
+    use super::Adaptive;
+    use crate::elements::scalar_mul;
+    use crate::twobody::{max_error, split_state, test_system, State};
+
+    let system = test_system();
+    let t_end = 2.345;
+
+    // The last value lands exactly on `t_end` and is accurate
+    let check = |solver: &mut dyn Iterator<Item = State<2>>, tol: f64| {
+        let mut last = None;
+        for state in solver {
+            assert!(state[0] <= t_end);
+            last = Some(state);
+        }
+        let last = last.unwrap();
+        assert_eq!(last[0], t_end);
+        assert!(max_error(&system, last) < tol);
+    };
+
+    check(
+        &mut system.terminate(system.construct_rk4(0.01)).end(t_end),
+        1e-7,
+    );
+    check(
+        &mut system.terminate(system.construct_heun(0.001)).end(t_end),
+        1e-4,
+    );
+    check(
+        &mut system
+            .terminate(system.construct_rk45(0.01, 1e-10, f64::INFINITY))
+            .end(t_end),
+        1e-7,
+    );
+    check(
+        &mut system
+            .terminate(system.construct_dopri5(0.01, 1e-10, f64::INFINITY))
+            .end(t_end),
+        1e-8,
+    );
+    check(
+        &mut system
+            .terminate(system.construct_gbs(0.01, 1e-10, f64::INFINITY))
+            .end(t_end),
+        1e-8,
+    );
+    check(
+        &mut system.terminate(system.construct_ab2(0.001)).end(t_end),
+        1e-4,
+    );
+    check(
+        &mut system.terminate(system.construct_abm(0.01, 4)).end(t_end),
+        1e-6,
+    );
+    check(
+        &mut system.terminate(system.construct_verlet(0.001)).end(t_end),
+        1e-5,
+    );
+    check(
+        &mut system
+            .terminate(system.construct_gauss_legendre(0.01, 2))
+            .end(t_end),
+        1e-7,
+    );
+    check(
+        &mut system
+            .terminate(system.construct_universal(0.01).unwrap())
+            .end(t_end),
+        1e-12,
+    );
+    check(
+        &mut system
+            .terminate(system.construct_kepler(0.01).unwrap())
+            .end(t_end),
+        1e-12,
+    );
+
+    // Nested adapters stop at the nearest end time
+    let nested = |solver: &mut dyn Solver<Item = State<2>>| {
+        let inner = system.terminate(solver).end(1.0);
+        let values: Vec<_> = system.terminate(inner).end(2.0).take(100).collect();
+
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[3][0], 1.0);
+        assert!(max_error(&system, values[3]) < 1e-12);
+    };
+
+    nested(&mut system.construct_kepler(0.3).unwrap());
+    nested(&mut system.construct_universal(0.3).unwrap());
+
+    // Nothing is returned at or after the end time
+    for &t_end in &[-1.0, 0.0] {
+        let mut solver = system.terminate(system.construct_rk4(0.1)).end(t_end);
+        assert!(solver.next().is_none());
+        assert!(solver.reached());
+
+        let mut rk45 = system.construct_rk45(0.1, 1e-10, f64::INFINITY);
+        let mut solver = system.terminate(&mut rk45).end(t_end);
+        assert!(solver.next().is_none());
+        assert!(solver.reached());
+        assert!(rk45.error().is_none());
+        assert_eq!(rk45.stats().rhs_calls, 0);
+    }
+
+    // Max number of steps
+    let mut solver = system.terminate(system.construct_rk4(0.01)).max_steps(50);
+    assert_eq!(solver.by_ref().count(), 50);
+    assert_eq!(solver.steps(), 50);
+    assert!(!solver.reached());
+
+    // The end time is reached before the max number of steps
+    let mut solver = system
+        .terminate(system.construct_rk4(0.01))
+        .end(0.1)
+        .max_steps(50);
+    assert_eq!(solver.by_ref().count(), 10);
+    assert!(solver.reached());
+
+    // Terminal event stops before the end time
+    let mut solver = system
+        .terminate(system.events(
+            system.construct_dopri5(0.01, 1e-10, f64::INFINITY),
+            vec![system.periapsis().terminal()],
+        ))
+        .end(100.0);
+    let last = solver.by_ref().last().unwrap();
+    assert!(!solver.reached());
+    assert!(last[0] < 100.0);
+    let (r, v) = split_state::<2>(&last);
+    assert!(scalar_mul(r, v).abs() < 1e-8);
+}
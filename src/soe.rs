@@ -9,35 +9,52 @@ pub trait Soe {
     fn call(&mut self, args: &Self::Args) -> Self::Args;
 }
 
+/// System of equations which is split into two parts
+/// `call(x) = call_f1(x) + call_f2(x)` where
+///
+/// `call_f1` - derivatives of positions, depends on velocities only
+///
+/// `call_f2` - derivatives of velocities, depends on positions only
+///
+/// Split is used by symplectic methods
+pub trait SplitSoe: Soe {
+    fn call_f1(&mut self, args: &Self::Args) -> Self::Args;
+
+    fn call_f2(&mut self, args: &Self::Args) -> Self::Args;
+}
+
 /// System of two equations for main problem
 ///
 /// `F1` and `F2` - two functions
 ///
+/// A - type of functions arguments (and of the system output)
+///
 /// T - inherit type of vectors
 ///
-/// N1 - length of functions arguments
+/// N - length of functions outputs
 ///
-/// N2 - length of functions outputs
+/// Output of `F1` and `F2` is placed at the end of the result,
+/// leading values are filled with default `T`
 ///
 /// Should be created from Soe2Builder
-pub struct Soe2<F1, F2, T, const N1: usize, const N2: usize> {
+pub struct Soe2<F1, F2, A, T, const N: usize> {
     f1: F1,
     f2: F2,
-    marker: PhantomData<(Vector<T, N1>, Vector<T, N2>)>,
+    marker: PhantomData<(A, Vector<T, N>)>,
 }
 
 /// Builder for Soe2
-pub struct Soe2Builder<T, const ArgLen: usize, const OutLen: usize> {
-    marker: PhantomData<(Vector<T, ArgLen>, Vector<T, OutLen>)>,
+pub struct Soe2Builder<A, T, const N: usize> {
+    marker: PhantomData<(A, Vector<T, N>)>,
 }
 
-impl<T, const N1: usize, const N2: usize> Soe2Builder<T, N1, N2> {
+impl<A, T, const N: usize> Soe2Builder<A, T, N> {
     pub fn new() -> Self {
         Self {
             marker: PhantomData,
         }
     }
-    pub fn build<F1, F2>(self, f1: F1, f2: F2) -> Soe2<F1, F2, T, N1, N2> {
+    pub fn build<F1, F2>(self, f1: F1, f2: F2) -> Soe2<F1, F2, A, T, N> {
         Soe2 {
             f1,
             f2,
@@ -46,14 +63,20 @@ impl<T, const N1: usize, const N2: usize> Soe2Builder<T, N1, N2> {
     }
 }
 
+impl<A, T, const N: usize> Default for Soe2Builder<A, T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// One equation (just for example)
 pub struct SimpleSoe<F1, T, const N1: usize, const N2: usize> {
     f1: F1,
     marker: PhantomData<(Vector<T, N1>, Vector<T, N2>)>,
 }
 
-pub struct SimpleSoeBuilder<T, const ArgLen: usize, const OutLen: usize> {
-    marker: PhantomData<(Vector<T, ArgLen>, Vector<T, OutLen>)>,
+pub struct SimpleSoeBuilder<T, const N1: usize, const N2: usize> {
+    marker: PhantomData<(Vector<T, N1>, Vector<T, N2>)>,
 }
 
 impl<T, const N1: usize, const N2: usize> SimpleSoeBuilder<T, N1, N2> {
@@ -70,28 +93,74 @@ impl<T, const N1: usize, const N2: usize> SimpleSoeBuilder<T, N1, N2> {
     }
 }
 
-impl<F1, F2, T, const ArgLen: usize, const OutLen: usize> Soe for Soe2<F1, F2, T, ArgLen, OutLen>
+impl<T, const N1: usize, const N2: usize> Default for SimpleSoeBuilder<T, N1, N2> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F1, F2, A, T, const N: usize> Soe for Soe2<F1, F2, A, T, N>
 where
-    F1: FnMut(&Vector<T, ArgLen>) -> Vector<T, OutLen>,
-    F2: FnMut(&Vector<T, ArgLen>) -> Vector<T, OutLen>,
+    F1: FnMut(&A) -> Vector<T, N>,
+    F2: FnMut(&A) -> Vector<T, N>,
+    A: Default + AsMut<[T]>,
     T: Default + Copy,
 {
-    type Args = Vector<T, ArgLen>;
+    type Args = A;
 
     fn call(&mut self, args: &Self::Args) -> Self::Args {
         let f1result = (self.f1)(args);
         let f2result = (self.f2)(args);
 
-        Self::Args::construct_from_two(&f1result, &f2result)
+        let mut result = A::default();
+        let data = result.as_mut();
+        let start = data.len() - 2 * N;
+
+        data[start..start + N].copy_from_slice(&f1result);
+        data[start + N..].copy_from_slice(&f2result);
+
+        result
+    }
+}
+
+impl<F1, F2, A, T, const N: usize> SplitSoe for Soe2<F1, F2, A, T, N>
+where
+    F1: FnMut(&A) -> Vector<T, N>,
+    F2: FnMut(&A) -> Vector<T, N>,
+    A: Default + AsMut<[T]>,
+    T: Default + Copy,
+{
+    fn call_f1(&mut self, args: &Self::Args) -> Self::Args {
+        let f1result = (self.f1)(args);
+
+        let mut result = A::default();
+        let data = result.as_mut();
+        let start = data.len() - 2 * N;
+
+        data[start..start + N].copy_from_slice(&f1result);
+
+        result
+    }
+
+    fn call_f2(&mut self, args: &Self::Args) -> Self::Args {
+        let f2result = (self.f2)(args);
+
+        let mut result = A::default();
+        let data = result.as_mut();
+        let start = data.len() - N;
+
+        data[start..].copy_from_slice(&f2result);
+
+        result
     }
 }
 
-impl<F1, T, const ArgLen: usize, const OutLen: usize> Soe for SimpleSoe<F1, T, ArgLen, OutLen>
+impl<F1, T, const N1: usize, const N2: usize> Soe for SimpleSoe<F1, T, N1, N2>
 where
-    F1: FnMut(&Vector<T, ArgLen>) -> Vector<T, OutLen>,
+    F1: FnMut(&Vector<T, N1>) -> Vector<T, N2>,
     T: Default + Copy,
 {
-    type Args = Vector<T, ArgLen>;
+    type Args = Vector<T, N1>;
 
     fn call(&mut self, args: &Self::Args) -> Self::Args {
         let f1result = (self.f1)(args);
@@ -1,15 +1,68 @@
-use std::fmt::Debug;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Div, Mul, Sub};
 
 use crate::{
-    methods::{ab2::Ab2, am2::Am2, euler::Euler, rk4::Rk4, rk45::Rk45},
-    soe::{Soe, Soe2, Soe2Builder},
-    vector::{Vector, Vector2, Vector3, Vector5, Vector7},
-    kepler::Kepler
+    elements::{scalar_mul, vec_len, OrbitalElements},
+    invariants::{Invariants, Monitor},
+    kepler::{Kepler, KeplerError},
+    methods::{
+        ab2::Ab2,
+        abm::Abm,
+        am2::Am2,
+        dopri5::Dopri5,
+        euler::{ExplicitEuler, Heun, ImplicitEuler},
+        events::{Direction, Event, Events},
+        explicit_rk::ExplicitRk,
+        gauss::GaussLegendre,
+        gbs::Gbs,
+        output::Output,
+        rk4::Rk4,
+        rk45::Rk45,
+        rkn::{Rkn, RKN4, RKN64},
+        symplectic::{Symplectic, VERLET, YOSHIDA4, YOSHIDA6},
+        tableau::Tableau,
+        terminate::Terminate,
+        Solver, Stats, Tolerance,
+    },
+    soe::{Soe, Soe2Builder, SplitSoe},
+    universal::Universal,
+    vector::{Vector, Vector3, Vector7},
 };
 
 pub type VType = f64;
 
+/// Dimension of the space where bodies move
+#[derive(Debug, Clone, Copy)]
+pub struct Dim<const N: usize>;
+
+/// Ties the space dimension to the state vector
+/// (t, r, v) of length `2 * N + 1` which is used by solvers
+///
+/// Support of a new dimension is just one more impl
+/// for `Dim<N>`
+pub trait Space {
+    type State: Debug
+        + Default
+        + Copy
+        + AsRef<[VType]>
+        + AsMut<[VType]>
+        + Add<Output = Self::State>
+        + Sub<Output = Self::State>
+        + Mul<VType, Output = Self::State>
+        + Div<VType, Output = Self::State>;
+}
+
+impl Space for Dim<2> {
+    type State = Vector<VType, 5>;
+}
+
+impl Space for Dim<3> {
+    type State = Vector<VType, 7>;
+}
+
+/// State vector (t, r, v) for `N` dimension
+pub type State<const N: usize> = <Dim<N> as Space>::State;
+
 /// One of the body with initial parameters
 #[derive(Debug, Clone, Copy)]
 pub struct Body<const N: usize> {
@@ -58,21 +111,96 @@ impl<const N: usize> Display for Position<N> {
     }
 }
 
-impl TwoBodyReader<2> {
-    pub fn get(&self, data: Vector<VType, 5>) -> Position<2> {
-        let r: Vector<VType, 2> = [data[1], data[2]].into();
+/// Take `N` values of `state` starting from position `start`
+fn take<const N: usize>(state: &[VType], start: usize) -> Vector<VType, N> {
+    let mut result = Vector::<VType, N>::new();
+    result.copy_from_slice(&state[start..start + N]);
+    result
+}
 
-        let body1 = ((self.a * data[0] + self.b) - r * self.m2) / (self.m1 + self.m2);
+/// Pad (or cut) vector to 3d space
+fn to_vector3<const N: usize>(vec: &Vector<VType, N>) -> Vector3 {
+    let mut result = Vector3::new();
+    Vector3::fill_from_vector(&mut result, 0, vec);
+    result
+}
 
-        let body2 = ((self.a * data[0] + self.b) + r * self.m1) / (self.m1 + self.m2);
+/// Convert 3d state (t, x, y, z, vx, vy, vz) to the state of `N` dimension
+fn from_state3<const N: usize>(state: Vector7) -> State<N>
+where
+    Dim<N>: Space,
+{
+    let mut result = State::<N>::default();
+    let data = result.as_mut();
 
-        Position { body1, body2 }
+    data[0] = state[0];
+    data[1..N + 1].copy_from_slice(&state[1..N + 1]);
+    data[N + 1..].copy_from_slice(&state[4..N + 4]);
+
+    result
+}
+
+/// Analytic solver with values converted to the state of `N` dimension
+struct Analytic<I, const N: usize> {
+    solver: I,
+}
+
+impl<I, const N: usize> Iterator for Analytic<I, N>
+where
+    I: Iterator<Item = Vector7>,
+    Dim<N>: Space,
+{
+    type Item = State<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.solver.next().map(from_state3::<N>)
     }
 }
 
-impl TwoBodyReader<3> {
-    pub fn get(&self, data: Vector<VType, 7>) -> Position<3> {
-        let r: Vector<VType, 3> = [data[1], data[2], data[3]].into();
+impl<I, const N: usize> Solver for Analytic<I, N>
+where
+    I: Solver<Item = Vector7>,
+    Dim<N>: Space,
+{
+    fn stats(&self) -> Stats {
+        self.solver.stats()
+    }
+
+    fn current(&self) -> State<N> {
+        from_state3::<N>(self.solver.current())
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.solver.clip(h);
+    }
+
+    fn dense(&self, t: f64) -> Option<State<N>> {
+        self.solver.dense(t).map(from_state3::<N>)
+    }
+
+    fn has_dense(&self) -> bool {
+        self.solver.has_dense()
+    }
+}
+
+/// Split `state` to position and velocity padded (or cut) to 3d space
+pub(crate) fn split_state<const N: usize>(state: &State<N>) -> (Vector3, Vector3)
+where
+    Dim<N>: Space,
+{
+    let r = take::<N>(state.as_ref(), 1);
+    let v = take::<N>(state.as_ref(), N + 1);
+
+    (to_vector3(&r), to_vector3(&v))
+}
+
+impl<const N: usize> TwoBodyReader<N>
+where
+    Dim<N>: Space,
+{
+    pub fn get(&self, data: State<N>) -> Position<N> {
+        let data = data.as_ref();
+        let r = take::<N>(data, 1);
 
         let body1 = ((self.a * data[0] + self.b) - r * self.m2) / (self.m1 + self.m2);
 
@@ -88,40 +216,45 @@ impl<const N: usize> TwoBodySystem<N> {
     }
 }
 
-impl TwoBodySystem<2> {
+impl<const N: usize> TwoBodySystem<N>
+where
+    Dim<N>: Space,
+{
     /// Generate system of equations
-    pub fn generate_soe(self) -> impl Soe<Args = Vector<VType, 5>> {
-        let f1 = |args: &Vector<VType, 5>| Vector::<VType, 2> {
-            data: [args[3], args[4]],
-        };
+    pub fn generate_soe(self) -> impl SplitSoe<Args = State<N>> {
+        let f1 = |args: &State<N>| take::<N>(args.as_ref(), N + 1);
 
-        let f2 = move |args: &Vector<VType, 5>| {
-            let _result = Vector::<VType, 2>::new();
-            let r: Vector<VType, 2> = [args[1], args[2]].into();
+        let f2 = move |args: &State<N>| {
+            let r = take::<N>(args.as_ref(), 1);
 
-            let sum_sq = r[0].powi(2) + r[1].powi(2);
+            let sum_sq: VType = r.iter().map(|x| x * x).sum();
             let len_inpow3 = sum_sq * sum_sq.sqrt();
 
             r * (self.body1.m + self.body2.m) * -self.g / len_inpow3
         };
 
-        Soe2Builder::<f64, 5, 2>::new().build(f1, f2)
+        Soe2Builder::<State<N>, VType, N>::new().build(f1, f2)
     }
 
     /// Get init vector
-    /// (t0, r0x, r0y, v0x, v0y)
+    /// (t0, r0, v0)
     /// where
     ///
     /// t0 - start of time
     ///
-    /// (r0x, r0y) - initial position of vector between `body1` and `body2`
+    /// r0 - initial position of vector between `body1` and `body2`
+    /// (`N` components)
     ///
-    /// (v0x, v0y) - initial speed of vector between `body` and `body2`
-    pub fn get_init(&self) -> Vector<VType, 5> {
-        Vector::<VType, 5>::construct_from_two(
-            &(self.body2.pos - self.body1.pos),
-            &(self.body2.velocity - self.body1.velocity),
-        )
+    /// v0 - initial speed of vector between `body` and `body2`
+    /// (`N` components)
+    pub fn get_init(&self) -> State<N> {
+        let mut result = State::<N>::default();
+        let data = result.as_mut();
+
+        data[1..N + 1].copy_from_slice(&(self.body2.pos - self.body1.pos));
+        data[N + 1..].copy_from_slice(&(self.body2.velocity - self.body1.velocity));
+
+        result
     }
 
     /// Calculation of center of mass movement
@@ -129,8 +262,8 @@ impl TwoBodySystem<2> {
     ///
     /// # Returns
     ///
-    /// ( (a1, a2), (b1, b2)) vector
-    pub fn calc_center(&self) -> (Vector<VType, 2>, Vector<VType, 2>) {
+    /// (A, B) vectors with `N` components
+    pub fn calc_center(&self) -> (Vector<VType, N>, Vector<VType, N>) {
         (
             ((self.body1.velocity * self.body1.m + self.body2.velocity * self.body2.m)
                 / (self.body1.m + self.body2.m)),
@@ -139,7 +272,7 @@ impl TwoBodySystem<2> {
         )
     }
 
-    pub fn build_reader(&self) -> TwoBodyReader<2> {
+    pub fn build_reader(&self) -> TwoBodyReader<N> {
         let center = self.calc_center();
         TwoBodyReader {
             a: center.0,
@@ -149,140 +282,524 @@ impl TwoBodySystem<2> {
         }
     }
 
-    /// Construct rk4 solver with `h` step
-    pub fn construct_rk4(&self, h: VType) -> impl Iterator<Item = Vector<VType, 5>> {
-        Rk4::new(self.get_init(), self.generate_soe(), h)
+    /// Std gravitational parameter of the relative motion
+    pub fn mu(&self) -> VType {
+        self.g * (self.body1.m + self.body2.m)
     }
 
-    pub fn construct_euler(&self, h: VType) -> impl Iterator<Item = Vector<VType, 5>> {
-        Euler::new(self.get_init(), self.generate_soe(), h)
+    /// Invariants of the relative motion for `state`
+    ///
+    /// `N` must not be greater than 3
+    pub fn invariants(&self, state: State<N>) -> Invariants {
+        assert!(N <= 3, "Invariants are defined for N <= 3");
+
+        let (r, v) = split_state::<N>(&state);
+        Invariants::new(r, v, self.mu())
     }
 
-    pub fn construct_rk45(
-        &self,
-        h: VType,
-        e: VType,
-        max: VType,
-    ) -> impl Iterator<Item = Vector<VType, 5>> {
-        Rk45::new(self.get_init(), self.generate_soe(), h, e, max)
+    /// Specific orbital energy for `state`
+    pub fn energy(&self, state: State<N>) -> VType {
+        self.invariants(state).energy
     }
 
-    pub fn construct_ab2(
-        &self,
-        h: VType,
-        init2: Vector<VType, 5>,
-    ) -> impl Iterator<Item = Vector<VType, 5>> {
-        Ab2::new(self.get_init(), init2, self.generate_soe(), h)
+    /// Specific angular momentum for `state`
+    pub fn angular_momentum(&self, state: State<N>) -> Vector3 {
+        self.invariants(state).angular_momentum
     }
 
-    pub fn construct_am2(
-        &self,
-        h: VType,
-        init2: Vector<VType, 5>,
-    ) -> impl Iterator<Item = Vector<VType, 5>> {
-        Am2::new(self.get_init(), init2, self.generate_soe(), h)
+    /// Eccentricity (Laplace–Runge–Lenz) vector for `state`
+    pub fn eccentricity_vector(&self, state: State<N>) -> Vector3 {
+        self.invariants(state).eccentricity
     }
 
-    pub fn construct_kepler(
+    /// Wrap `solver` to report the drift of invariants
+    /// from their init values for every step
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use two_body::{Body2d, TwoBodySystem2d};
+    ///
+    /// let body1 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [0.0, 0.0].into(),
+    ///     velocity: [0.5, 0.0].into(),
+    /// };
+    ///
+    /// let body2 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [1.0, 1.0].into(),
+    ///     velocity: [-0.5, 0.0].into(),
+    /// };
+    ///
+    /// let system = TwoBodySystem2d::new(body1, body2, 0.1);
+    ///
+    /// for (_state, drift) in system.monitor(system.construct_heun(0.01)).take(100) {
+    ///     assert!(drift.energy < 1e-3);
+    /// }
+    /// ```
+    pub fn monitor<I>(&self, solver: I) -> Monitor<I, N>
+    where
+        I: Iterator<Item = State<N>>,
+    {
+        Monitor::new(solver, self.invariants(self.get_init()), self.mu())
+    }
+
+    /// Wrap `solver` to detect `events`, see `Events`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use two_body::{Body2d, TwoBodySystem2d};
+    ///
+    /// let body1 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [0.0, 0.0].into(),
+    ///     velocity: [0.5, 0.0].into(),
+    /// };
+    ///
+    /// let body2 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [1.0, 1.0].into(),
+    ///     velocity: [-0.5, 0.0].into(),
+    /// };
+    ///
+    /// let system = TwoBodySystem2d::new(body1, body2, 0.1);
+    ///
+    /// let mut solver = system.events(
+    ///     system.construct_rk4(0.01),
+    ///     vec![system.periapsis().terminal()],
+    /// );
+    /// let last = solver.by_ref().last().unwrap();
+    ///
+    /// assert!(solver.stopped());
+    /// assert_eq!(solver.occurred()[0].t, last[0]);
+    /// ```
+    pub fn events<'a, I>(
         &self,
-        h: VType
-    ) -> impl Iterator<Item = Vector<VType, 5>> {
-        let init = self.get_init();
-        Kepler::new([init[1], init[2], 0.0].into(), [init[3], init[4], 0.0].into(), self.g * (self.body1.m + self.body2.m), h)
+        solver: I,
+        events: Vec<Event<'a, State<N>>>,
+    ) -> Events<'a, I, State<N>, impl Soe<Args = State<N>>>
+    where
+        I: Solver<Item = State<N>>,
+    {
+        Events::new(solver, self.generate_soe(), events)
     }
-}
 
-impl TwoBodySystem<3> {
-    // Generate system of equations
-    pub fn generate_soe(self) -> impl Soe<Args = Vector<VType, 7>> {
-        let f1 = |args: &Vector<VType, 7>| Vector::<VType, 3> {
-            data: [args[4], args[5], args[6]],
-        };
+    /// Values of `solver` exactly at `times`, see `Output`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use two_body::{grid, Body2d, TwoBodySystem2d};
+    ///
+    /// let body1 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [0.0, 0.0].into(),
+    ///     velocity: [0.5, 0.0].into(),
+    /// };
+    ///
+    /// let body2 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [1.0, 1.0].into(),
+    ///     velocity: [-0.5, 0.0].into(),
+    /// };
+    ///
+    /// let system = TwoBodySystem2d::new(body1, body2, 0.1);
+    ///
+    /// let solver = system.construct_rk45(0.01, 1e-10, f64::INFINITY);
+    /// let values: Vec<_> = system.output(solver, grid(0.0, 0.25, 5)).collect();
+    ///
+    /// assert_eq!(values.len(), 5);
+    /// assert_eq!(values[3][0], 0.75);
+    /// ```
+    pub fn output<I, J>(&self, solver: I, times: J) -> Output<I, J::IntoIter, State<N>>
+    where
+        I: Solver<Item = State<N>>,
+        J: IntoIterator<Item = f64>,
+    {
+        Output::new(solver, times)
+    }
 
-        let f2 = move |args: &Vector<VType, 7>| {
-            let _result = Vector::<VType, 3>::new();
-            let r: Vector<VType, 3> = [args[1], args[2], args[3]].into();
+    /// Stop `solver` at the end time or after the max number
+    /// of steps, see `Terminate`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use two_body::{Body2d, TwoBodySystem2d};
+    ///
+    /// let body1 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [0.0, 0.0].into(),
+    ///     velocity: [0.5, 0.0].into(),
+    /// };
+    ///
+    /// let body2 = Body2d {
+    ///     m: 5.0,
+    ///     pos: [1.0, 1.0].into(),
+    ///     velocity: [-0.5, 0.0].into(),
+    /// };
+    ///
+    /// let system = TwoBodySystem2d::new(body1, body2, 0.1);
+    ///
+    /// let solver = system.terminate(system.construct_rk4(0.3)).end(1.0);
+    /// let last = solver.last().unwrap();
+    ///
+    /// assert_eq!(last[0], 1.0);
+    /// ```
+    pub fn terminate<I>(&self, solver: I) -> Terminate<I>
+    where
+        I: Solver<Item = State<N>>,
+    {
+        Terminate::new(solver)
+    }
 
-            let sum_sq = r[0].powi(2) + r[1].powi(2) + r[2].powi(3);
-            let len_inpow3 = sum_sq * sum_sq.sqrt();
+    /// Event of periapsis passage: `r * v` changes sign
+    /// from negative to positive
+    pub fn periapsis(&self) -> Event<'static, State<N>> {
+        Event::new(|state: &State<N>| {
+            let (r, v) = split_state::<N>(state);
+            scalar_mul(r, v)
+        })
+        .direction(Direction::Rising)
+    }
 
-            r * (self.body1.m + self.body2.m) * -self.g / len_inpow3
-        };
+    /// Event of apoapsis passage: `r * v` changes sign
+    /// from positive to negative
+    pub fn apoapsis(&self) -> Event<'static, State<N>> {
+        Event::new(|state: &State<N>| {
+            let (r, v) = split_state::<N>(state);
+            scalar_mul(r, v)
+        })
+        .direction(Direction::Falling)
+    }
 
-        Soe2Builder::<VType, 7, 3>::new().build(f1, f2)
+    /// Event of the distance between the bodies crossing `distance`
+    ///
+    /// Collision with `radius` is `distance(radius)` with
+    /// `Direction::Falling` direction
+    pub fn distance(&self, distance: VType) -> Event<'static, State<N>> {
+        Event::new(move |state: &State<N>| {
+            let (r, _) = split_state::<N>(state);
+            vec_len(r) - distance
+        })
     }
 
-    /// Get init vector
-    /// (t0, r0x, r0y, r0z, v0x, v0y, v0z)
-    /// where
+    /// Event of crossing the plane through the center of mass
+    /// with `normal`, `Rising` is the crossing along `normal`
+    pub fn plane(&self, normal: Vector3) -> Event<'static, State<N>> {
+        Event::new(move |state: &State<N>| {
+            let (r, _) = split_state::<N>(state);
+            scalar_mul(r, normal)
+        })
+    }
+
+    /// Calculate orbital elements of the relative motion
+    /// from `state` returned by any solver
     ///
-    /// t0 - start of time
+    /// `N` must not be greater than 3
     ///
-    /// (r0x, r0y, r0z) - initial position of vector between `body1` and `body2`
+    /// # Errors
     ///
-    /// (v0x, v0y, v0z) - initial speed of vector between `body` and `body2`
-    pub fn get_init(&self) -> Vector<VType, 7> {
-        Vector::<VType, 7>::construct_from_two(
-            &(self.body2.pos - self.body1.pos),
-            &(self.body2.velocity - self.body1.velocity),
-        )
+    /// Returns `KeplerError` if the orbit is degenerate
+    pub fn elements(&self, state: State<N>) -> Result<OrbitalElements, KeplerError> {
+        assert!(N <= 3, "Orbital elements are defined for N <= 3");
+
+        let (r, v) = split_state::<N>(&state);
+        OrbitalElements::from_state(r, v, self.mu())
     }
 
-    /// Calculation of center of mass movement
-    /// R(t) = At + B
+    /// Construct rk4 solver with `h` step
+    pub fn construct_rk4(&self, h: VType) -> impl Solver<Item = State<N>> {
+        Rk4::new(self.get_init(), self.generate_soe(), h)
+    }
+
+    /// Construct explicit Euler solver with `h` step
+    pub fn construct_explicit_euler(&self, h: VType) -> impl Solver<Item = State<N>> {
+        ExplicitEuler::new(self.get_init(), self.generate_soe(), h)
+    }
+
+    /// Construct Heun's method solver with `h` step
+    pub fn construct_heun(&self, h: VType) -> impl Solver<Item = State<N>> {
+        Heun::new(self.get_init(), self.generate_soe(), h)
+    }
+
+    /// Construct implicit Euler solver with `h` step
     ///
-    /// # Returns
+    /// Solver is returned as is to set its iterations
+    /// and get the error which stopped it
+    pub fn construct_implicit_euler(
+        &self,
+        h: VType,
+    ) -> ImplicitEuler<State<N>, impl SplitSoe<Args = State<N>>> {
+        ImplicitEuler::new(self.get_init(), self.generate_soe(), h)
+    }
+
+    /// Construct explicit Runge–Kutta solver with `tableau`
+    /// and `h` step
     ///
-    /// ( (a1, a2, a3), (b1, b2, b3)) vector
-    pub fn calc_center(&self) -> (Vector<VType, 3>, Vector<VType, 3>) {
-        (
-            ((self.body1.velocity * self.body1.m + self.body2.velocity * self.body2.m)
-                / (self.body1.m + self.body2.m)),
-            ((self.body1.pos * self.body1.m + self.body2.pos * self.body2.m)
-                / (self.body1.m + self.body2.m)),
-        )
+    /// Solver is returned as is to switch it to adaptive step
+    pub fn construct_explicit_rk(
+        &self,
+        h: VType,
+        tableau: Tableau,
+    ) -> ExplicitRk<State<N>, impl SplitSoe<Args = State<N>>> {
+        ExplicitRk::new(self.get_init(), self.generate_soe(), h, tableau)
     }
 
-    pub fn build_reader(&self) -> TwoBodyReader<3> {
-        let center = self.calc_center();
-        TwoBodyReader {
-            a: center.0,
-            b: center.1,
-            m1: self.body1.m,
-            m2: self.body2.m,
-        }
+    /// Construct Runge–Kutta–Fehlberg 4(5) solver with init `h` step,
+    /// `tol` tolerance and `max` time
+    ///
+    /// Solver is returned as is to give access to its step limits
+    /// and the error which stopped it
+    pub fn construct_rk45(
+        &self,
+        h: VType,
+        tol: impl Into<Tolerance>,
+        max: VType,
+    ) -> Rk45<State<N>, impl SplitSoe<Args = State<N>>> {
+        Rk45::new(self.get_init(), self.generate_soe(), h, tol, max)
     }
-    /// Construct rk4 solver with `h` step
-    pub fn construct_rk4(&self, h: VType) -> impl Iterator<Item = Vector<VType, 7>> {
-        Rk4::new(self.get_init(), self.generate_soe(), h)
+
+    /// Construct Dormand–Prince 5(4) solver with init `h` step,
+    /// `tol` tolerance and `max` time
+    ///
+    /// Solver is returned as is to give access to the dense output
+    pub fn construct_dopri5(
+        &self,
+        h: VType,
+        tol: impl Into<Tolerance>,
+        max: VType,
+    ) -> Dopri5<State<N>, impl SplitSoe<Args = State<N>>> {
+        Dopri5::new(self.get_init(), self.generate_soe(), h, tol, max)
+    }
+
+    /// Construct Gragg–Bulirsch–Stoer solver with init `h` step,
+    /// `tol` tolerance and `max` time
+    ///
+    /// Solver is returned as is to set its max number of columns
+    pub fn construct_gbs(
+        &self,
+        h: VType,
+        tol: impl Into<Tolerance>,
+        max: VType,
+    ) -> Gbs<State<N>, impl SplitSoe<Args = State<N>>> {
+        Gbs::new(self.get_init(), self.generate_soe(), h, tol, max)
     }
 
-    pub fn construct_euler(&self, h: VType) -> impl Iterator<Item = Vector<VType, 7>> {
-        Euler::new(self.get_init(), self.generate_soe(), h)
+    /// Construct classical Runge–Kutta–Nyström 4th order solver
+    /// with `h` step
+    pub fn construct_rkn4(&self, h: VType) -> impl Solver<Item = State<N>> {
+        Rkn::new(self.get_init(), self.generate_soe(), h, RKN4)
     }
 
-    pub fn construct_rk45(
+    /// Construct Dormand–El-Mikkawy–Prince RKN6(4) solver with
+    /// init `h` step, `tol` tolerance and `max` time
+    ///
+    /// Solver is returned as is to get the error which stopped it
+    pub fn construct_rkn64(
         &self,
         h: VType,
-        e: VType,
+        tol: impl Into<Tolerance>,
         max: VType,
-    ) -> impl Iterator<Item = Vector<VType, 7>> {
-        Rk45::new(self.get_init(), self.generate_soe(), h, e, max)
+    ) -> Rkn<State<N>, impl SplitSoe<Args = State<N>>> {
+        Rkn::new(self.get_init(), self.generate_soe(), h, RKN64).adaptive(tol, max)
     }
 
-    pub fn construct_ab2(
+    /// Construct Adams–Bashforth 2nd order solver with `h` step
+    ///
+    /// Solver is returned as is to set its starter
+    pub fn construct_ab2(&self, h: VType) -> Ab2<State<N>, impl SplitSoe<Args = State<N>>> {
+        Ab2::new(self.get_init(), self.generate_soe(), h)
+    }
+
+    /// Construct Adams–Moulton 2-step solver with `h` step
+    ///
+    /// Solver is returned as is to set its starter
+    pub fn construct_am2(&self, h: VType) -> Am2<State<N>, impl SplitSoe<Args = State<N>>> {
+        Am2::new(self.get_init(), self.generate_soe(), h)
+    }
+
+    /// Construct Adams–Bashforth–Moulton solver of `order`
+    /// with `h` step
+    ///
+    /// Solver is returned as is to set its starter
+    /// or switch it to variable step and order
+    pub fn construct_abm(
         &self,
         h: VType,
-        init2: Vector<VType, 7>,
-    ) -> impl Iterator<Item = Vector<VType, 7>> {
-        Ab2::new(self.get_init(), init2, self.generate_soe(), h)
+        order: usize,
+    ) -> Abm<State<N>, impl SplitSoe<Args = State<N>>> {
+        Abm::new(self.get_init(), self.generate_soe(), h, order)
+    }
+
+    /// Construct Gauss–Legendre solver with `stages` and `h` step
+    ///
+    /// Solver is returned as is to set its iterations
+    /// and get the error which stopped it
+    pub fn construct_gauss_legendre(
+        &self,
+        h: VType,
+        stages: usize,
+    ) -> GaussLegendre<State<N>, impl SplitSoe<Args = State<N>>> {
+        GaussLegendre::new(self.get_init(), self.generate_soe(), h, stages)
+    }
+
+    /// Construct Störmer–Verlet (velocity Verlet) solver with `h` step
+    pub fn construct_verlet(&self, h: VType) -> impl Solver<Item = State<N>> {
+        Symplectic::new(self.get_init(), self.generate_soe(), h, &VERLET)
+    }
+
+    /// Construct 4th order Forest–Ruth (Yoshida) solver with `h` step
+    pub fn construct_yoshida4(&self, h: VType) -> impl Solver<Item = State<N>> {
+        Symplectic::new(self.get_init(), self.generate_soe(), h, &YOSHIDA4)
+    }
+
+    /// Construct 6th order Yoshida solver with `h` step
+    pub fn construct_yoshida6(&self, h: VType) -> impl Solver<Item = State<N>> {
+        Symplectic::new(self.get_init(), self.generate_soe(), h, &YOSHIDA6)
+    }
+
+    /// Construct analytic solver with `h` step
+    ///
+    /// Kepler's orbit is defined in 3d space, so
+    /// `N` must not be greater than 3
+    ///
+    /// # Errors
+    ///
+    /// Returns `KeplerError` if the orbit is degenerate
+    pub fn construct_kepler(&self, h: VType) -> Result<impl Solver<Item = State<N>>, KeplerError> {
+        assert!(N <= 3, "Kepler's orbit is defined for N <= 3");
+
+        let (r, v) = split_state::<N>(&self.get_init());
+        let solver = Kepler::new(r, v, self.mu(), h)?;
+
+        Ok(Analytic { solver })
     }
 
-    pub fn construct_am2(
+    /// Construct analytic solver with universal variables with `h` step
+    ///
+    /// `N` must not be greater than 3
+    ///
+    /// # Errors
+    ///
+    /// Returns `KeplerError::ZeroRadius` if the bodies are in the same position
+    pub fn construct_universal(
         &self,
         h: VType,
-        init2: Vector<VType, 7>,
-    ) -> impl Iterator<Item = Vector<VType, 7>> {
-        Am2::new(self.get_init(), init2, self.generate_soe(), h)
+    ) -> Result<impl Solver<Item = State<N>>, KeplerError> {
+        assert!(N <= 3, "Universal variables are defined for N <= 3");
+
+        let (r, v) = split_state::<N>(&self.get_init());
+        let solver = Universal::new(r, v, self.mu(), h)?;
+
+        Ok(Analytic { solver })
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn test_system() -> TwoBodySystem2d {
+    TwoBodySystem2d::new(
+        Body2d {
+            m: 5.0,
+            pos: [0.0, 0.0].into(),
+            velocity: [0.5, 0.0].into(),
+        },
+        Body2d {
+            m: 5.0,
+            pos: [1.0, 1.0].into(),
+            velocity: [-0.5, 0.0].into(),
+        },
+        0.1,
+    )
+}
+
+/// Max error of the position and the velocity in `state`
+/// of `system` from the analytic solution
+#[cfg(test)]
+pub(crate) fn max_error(system: &TwoBodySystem2d, state: State<2>) -> VType {
+    use crate::universal::propagate;
+
+    let (r0, v0) = split_state::<2>(&system.get_init());
+    let (r, v) = propagate(r0, v0, system.mu(), state[0]);
+    let (r1, v1) = split_state::<2>(&state);
+
+    (r1 - r)
+        .iter()
+        .chain((v1 - v).iter())
+        .fold(0.0, |m, x| m.max(x.abs()))
+}
+
+/// Order of the method seen on `system`: log2 of the ratio of errors
+/// at `t` time of solvers made by `construct` with `h` and `h / 2` steps
+#[cfg(test)]
+pub(crate) fn observed_order<I>(
+    system: &TwoBodySystem2d,
+    construct: impl Fn(VType) -> I,
+    h: VType,
+    t: VType,
+) -> VType
+where
+    I: Iterator<Item = State<2>>,
+{
+    let error = |h: VType| {
+        let steps = (t / h).round() as usize;
+        max_error(system, construct(h).nth(steps - 1).unwrap())
+    };
+
+    (error(h) / error(h / 2.0)).log2()
+}
+
+#[test]
+fn test_planar_3d_matches_2d() {
+    let system2d = test_system();
+
+    let system3d = TwoBodySystem3d::new(
+        Body3d {
+            m: 5.0,
+            pos: [0.0, 0.0, 0.0].into(),
+            velocity: [0.5, 0.0, 0.0].into(),
+        },
+        Body3d {
+            m: 5.0,
+            pos: [1.0, 1.0, 0.0].into(),
+            velocity: [-0.5, 0.0, 0.0].into(),
+        },
+        0.1,
+    );
+
+    let solver2d = system2d.construct_rk4(0.01);
+    let solver3d = system3d.construct_rk4(0.01);
+
+    for (s2, s3) in solver2d.zip(solver3d).take(1000) {
+        assert_eq!(s2.data, [s3[0], s3[1], s3[2], s3[4], s3[5]]);
+        assert_eq!(s3[3], 0.0);
+        assert_eq!(s3[6], 0.0);
+    }
+}
+
+#[test]
+fn test_analytic_matches_rk4() {
+    let system = test_system();
+
+    let rk4 = system.construct_rk4(0.001);
+    let kepler = system.construct_kepler(0.001).unwrap();
+    let universal = system.construct_universal(0.001).unwrap();
+
+    let init = system.elements(system.get_init()).unwrap();
+
+    for ((s1, s2), s3) in rk4.zip(kepler).zip(universal).take(5000) {
+        let elements = system.elements(s1).unwrap();
+        assert!((elements.p - init.p).abs() < 1e-6);
+        assert!((elements.e - init.e).abs() < 1e-6);
+        assert!((elements.w - init.w).abs() < 1e-6);
+
+        assert!((s1[0] - s2[0]).abs() < 1e-9);
+        assert!((s1[0] - s3[0]).abs() < 1e-9);
+        for i in 1..5 {
+            assert!((s1[i] - s2[i]).abs() < 1e-6);
+            assert!((s1[i] - s3[i]).abs() < 1e-6);
+        }
     }
 }
@@ -0,0 +1,313 @@
+use crate::elements::{scalar_mul, vec_len};
+use crate::kepler::KeplerError;
+use crate::methods::{Solver, Stats};
+use crate::vector::{Vector3, Vector7};
+use std::f64::consts::PI;
+
+/// Max iterations of Newton's method for the universal Kepler's equation
+const MAX_ITER: usize = 50;
+
+/// Relative tolerance of Newton's method for the universal Kepler's equation
+const DELTA: f64 = 1e-12;
+
+/// Below this `|z|` Stumpff functions are calculated with series
+const SERIES_LIMIT: f64 = 1e-3;
+
+/// Stumpff function C(z)
+///
+/// C(z) = (1 - cos(sqrt(z))) / z for z > 0
+///
+/// C(z) = (cosh(sqrt(-z)) - 1) / -z for z < 0
+///
+/// C(0) = 1 / 2
+pub fn stumpff_c(z: f64) -> f64 {
+    if z.abs() < SERIES_LIMIT {
+        1.0 / 2.0 - z / 24.0 + z * z / 720.0 - z * z * z / 40320.0
+    } else if z > 0.0 {
+        (1.0 - z.sqrt().cos()) / z
+    } else {
+        ((-z).sqrt().cosh() - 1.0) / -z
+    }
+}
+
+/// Stumpff function S(z)
+///
+/// S(z) = (sqrt(z) - sin(sqrt(z))) / sqrt(z)^3 for z > 0
+///
+/// S(z) = (sinh(sqrt(-z)) - sqrt(-z)) / sqrt(-z)^3 for z < 0
+///
+/// S(0) = 1 / 6
+pub fn stumpff_s(z: f64) -> f64 {
+    if z.abs() < SERIES_LIMIT {
+        1.0 / 6.0 - z / 120.0 + z * z / 5040.0 - z * z * z / 362880.0
+    } else if z > 0.0 {
+        let sz = z.sqrt();
+        (sz - sz.sin()) / sz.powi(3)
+    } else {
+        let sz = (-z).sqrt();
+        (sz.sinh() - sz) / sz.powi(3)
+    }
+}
+
+/// Advance position `r0` and velocity `v0` by `dt` time
+/// with std gravitational parameter `mu`
+///
+/// Universal variable `chi` is found from the universal Kepler's
+/// equation, then the state is obtained with Lagrange coefficients
+/// `f`, `g`, `fdot` and `gdot`. Works for every type of orbit.
+///
+/// # Returns
+///
+/// (r, v) after `dt`
+pub fn propagate(r0: Vector3, v0: Vector3, mu: f64, dt: f64) -> (Vector3, Vector3) {
+    let r0_len = vec_len(r0);
+    let sqrt_mu = mu.sqrt();
+    let rv = scalar_mul(r0, v0) / sqrt_mu;
+
+    // Reciprocal of the semi-major axis
+    let alpha = 2.0 / r0_len - scalar_mul(v0, v0) / mu;
+
+    // Motion is periodic for elliptic orbit
+    let dt = if alpha > 0.0 {
+        let period = 2.0 * PI / (mu * alpha.powi(3)).sqrt();
+        dt % period
+    } else {
+        dt
+    };
+
+    // 1. Initial guess of chi
+    let mut chi = if alpha > 0.0 {
+        sqrt_mu * alpha * dt
+    } else if alpha < 0.0 {
+        let a = 1.0 / alpha;
+        let sign = dt.signum();
+        let arg = -2.0 * mu * alpha * dt
+            / (scalar_mul(r0, v0) + sign * (-mu * a).sqrt() * (1.0 - r0_len * alpha));
+
+        if arg > 0.0 {
+            sign * (-a).sqrt() * arg.ln()
+        } else {
+            sqrt_mu * dt / r0_len
+        }
+    } else {
+        sqrt_mu * dt / r0_len
+    };
+
+    // 2. Solve universal Kepler's equation with Newton's method
+    for _ in 0..MAX_ITER {
+        let z = alpha * chi * chi;
+        let c = stumpff_c(z);
+        let s = stumpff_s(z);
+
+        let f = rv * chi * chi * c + (1.0 - alpha * r0_len) * chi.powi(3) * s + r0_len * chi
+            - sqrt_mu * dt;
+        let df = rv * chi * (1.0 - z * s) + (1.0 - alpha * r0_len) * chi * chi * c + r0_len;
+
+        let delta = f / df;
+        chi -= delta;
+
+        if delta.abs() <= DELTA * chi.abs().max(1.0) {
+            break;
+        }
+    }
+
+    // 3. Lagrange coefficients
+    let z = alpha * chi * chi;
+    let c = stumpff_c(z);
+    let s = stumpff_s(z);
+
+    let f = 1.0 - chi * chi / r0_len * c;
+    let g = dt - chi.powi(3) / sqrt_mu * s;
+
+    let r = r0 * f + v0 * g;
+    let r_len = vec_len(r);
+
+    let fdot = sqrt_mu / (r_len * r0_len) * (alpha * chi.powi(3) * s - chi);
+    let gdot = 1.0 - chi * chi / r_len * c;
+
+    let v = r0 * fdot + v0 * gdot;
+
+    (r, v)
+}
+
+/// Analytic solver with universal variables
+///
+/// In contrast to `Kepler` it does not use orbital elements,
+/// so there are no singularities for circular or equatorial orbits
+#[derive(Debug)]
+pub struct Universal {
+    // Init position
+    r0: Vector3,
+    // Init velocity
+    v0: Vector3,
+    // std gravitational parameter
+    mu: f64,
+
+    // Init time
+    t0: f64,
+    // Time of the next value
+    t: f64,
+    // Time step
+    step: f64,
+    // Max length of the next step
+    clip: f64,
+    stats: Stats,
+}
+
+impl Universal {
+    /// Create solver from position `r` and velocity `v`
+    /// with std gravitational parameter `mu`
+    ///
+    /// Iterator returns values with `step` time step
+    pub fn new(r: Vector3, v: Vector3, mu: f64, step: f64) -> Result<Self, KeplerError> {
+        if mu <= 0.0 || mu.is_nan() {
+            return Err(KeplerError::NonPositiveMu(mu));
+        }
+
+        if vec_len(r) == 0.0 {
+            return Err(KeplerError::ZeroRadius);
+        }
+
+        Ok(Self {
+            r0: r,
+            v0: v,
+            mu,
+            t0: 0.0,
+            t: 0.0 + step,
+            step,
+            clip: f64::INFINITY,
+            stats: Stats::default(),
+        })
+    }
+
+    /// Set time of the next value, the following values
+    /// are returned with `step` after it (same as `Kepler`)
+    pub fn set_current_time(&mut self, t: f64) {
+        self.t = t;
+    }
+
+    // State [t, x, y, z, vx, vy, vz] at `t` time
+    fn state(&self, t: f64) -> Vector7 {
+        let (r, v) = propagate(self.r0, self.v0, self.mu, t - self.t0);
+
+        [t, r[0], r[1], r[2], v[0], v[1], v[2]].into()
+    }
+}
+
+impl Iterator for Universal {
+    type Item = Vector7;
+
+    // Return Vector7 = [t, x, y, z, vx, vy, vz]
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = if self.clip < self.step {
+            self.t - self.step + self.clip
+        } else {
+            self.t
+        };
+
+        self.t = t + self.step;
+        self.clip = f64::INFINITY;
+
+        self.stats.accepted += 1;
+
+        Some(self.state(t))
+    }
+}
+
+impl Solver for Universal {
+    fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn current(&self) -> Vector7 {
+        self.state(self.t - self.step)
+    }
+
+    fn clip(&mut self, h: f64) {
+        self.clip = self.clip.min(h);
+    }
+
+    /// Exact solution at any `t` time
+    fn dense(&self, t: f64) -> Option<Vector7> {
+        Some(self.state(t))
+    }
+
+    fn has_dense(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_universal_errors() {
+    let r = Vector3 { data: [1.0, 0.0, 0.0] };
+    let v = Vector3 { data: [0.0, 1.0, 0.0] };
+
+    let err = Universal::new(r, v, 0.0, 0.1).unwrap_err();
+    assert_eq!(err, KeplerError::NonPositiveMu(0.0));
+
+    let err = Universal::new(Vector3::new(), v, 1.0, 0.1).unwrap_err();
+    assert_eq!(err, KeplerError::ZeroRadius);
+    assert_eq!(err.to_string(), "bodies are in the same point");
+
+    // Radial motion is supported
+    assert!(Universal::new(r, [2.0, 0.0, 0.0].into(), 1.0, 0.1).is_ok());
+}
+
+#[test]
+fn test_stumpff_continuity() {
+    for &z in &[SERIES_LIMIT, -SERIES_LIMIT] {
+        let below = z * (1.0 - 1e-9);
+        let above = z * (1.0 + 1e-9);
+
+        assert!((stumpff_c(below) - stumpff_c(above)).abs() < 1e-12);
+        assert!((stumpff_s(below) - stumpff_s(above)).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_propagate() {
+    // Circular equatorial orbit: quarter of period
+    let r0: Vector3 = [1.0, 0.0, 0.0].into();
+    let v0: Vector3 = [0.0, 1.0, 0.0].into();
+
+    let (r, v) = propagate(r0, v0, 1.0, PI / 2.0);
+
+    assert!(vec_len(r - Vector3::from([0.0, 1.0, 0.0])) < 1e-10);
+    assert!(vec_len(v - Vector3::from([-1.0, 0.0, 0.0])) < 1e-10);
+
+    // Every type of orbit returns back
+    for &speed in &[0.5, 1.0, 2.0f64.sqrt(), 3.0] {
+        let r0: Vector3 = [1.0, 0.2, -0.3].into();
+        let v0: Vector3 = [0.1, speed, 0.2].into();
+
+        let (r, v) = propagate(r0, v0, 1.0, 7.5);
+        let (r, v) = propagate(r, v, 1.0, -7.5);
+
+        assert!(vec_len(r - r0) < 1e-8);
+        assert!(vec_len(v - v0) < 1e-8);
+    }
+}
+
+#[test]
+fn test_set_current_time() {
+    use crate::kepler::Kepler;
+
+    let r = Vector3 { data: [1.0, 0.0, 0.0] };
+    let v = Vector3 { data: [0.0, 1.2, 0.0] };
+
+    let mut kepler = Kepler::new(r, v, 1.0, 0.1).unwrap();
+    let mut universal = Universal::new(r, v, 1.0, 0.1).unwrap();
+
+    // The next values are at the set time
+    kepler.set_current_time(1.0);
+    universal.set_current_time(1.0);
+
+    for (a, b) in kepler.zip(universal).take(3) {
+        assert_eq!(a[0], b[0]);
+        assert!((0..7).all(|i| (a[i] - b[i]).abs() < 1e-12));
+    }
+
+    let mut universal = Universal::new(r, v, 1.0, 0.1).unwrap();
+    universal.set_current_time(1.0);
+    assert_eq!(universal.next().unwrap()[0], 1.0);
+}
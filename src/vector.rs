@@ -1,6 +1,6 @@
 use std::convert::{AsMut, AsRef, From};
 use std::fmt::Display;
-use std::ops::{Add, Deref, DerefMut, Div, Mul, Sub, Neg};
+use std::ops::{Add, Deref, DerefMut, Div, Mul, Sub};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Vector<T, const N: usize> {
@@ -33,10 +33,12 @@ where
     /// # Examples
     ///
     /// ```
+    /// use two_body::vector::Vector;
+    ///
     /// let v1 = Vector::<i32, 2> { data: [1, 2] };
     /// let v2 = Vector::<i32, 3> { data: [3, 4, 5] };
     ///
-    /// let res1 = Vector::<i32, 5>::construct_from_two(v1, v2);
+    /// let res1 = Vector::<i32, 5>::construct_from_two(&v1, &v2);
     /// assert_eq!(res1.data, [1, 2, 3, 4, 5]);
     /// ```
     pub fn construct_from_two<const N1: usize, const N2: usize>(
@@ -69,12 +71,13 @@ where
     /// # Examples
     ///
     /// ```
-    /// let v1 = Vector::<i32, 2> { data: [1, 2] };
-    /// let v2 = Vector::<i32, 3>::new();
+    /// use two_body::vector::Vector;
     ///
-    /// Vector::<i32, 3>::fill_from_vector(v2, 0, v1);
-    /// assert_eq(v2.data, [1, 2, 0]);
+    /// let v1 = Vector::<i32, 2> { data: [1, 2] };
+    /// let mut v2 = Vector::<i32, 3>::new();
     ///
+    /// Vector::<i32, 3>::fill_from_vector(&mut v2, 0, &v1);
+    /// assert_eq!(v2.data, [1, 2, 0]);
     /// ```
     pub fn fill_from_vector<const N1: usize>(
         target: &mut Vector<T, N>,
@@ -211,7 +214,7 @@ where
     T: Display,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.len() >= 1 {
+        if !self.is_empty() {
             write!(f, "{}", self.data[0])?;
         }
 